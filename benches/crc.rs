@@ -0,0 +1,43 @@
+//! CRC16 throughput over VBUS push sized payloads
+//!
+//! Run with all the implementations:
+//!
+//! ```sh
+//! cargo bench --bench crc --features crc16-slice4,crc16-slice8
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use robomaster_s1_proto::crc::{self, RM_S1_CRC16_SEED};
+
+const SIZES: [usize; 4] = [13, 64, 256, 1023];
+
+type Crc16Fn = fn(u16, &[u8]) -> u16;
+
+fn bench_crc16(c: &mut Criterion) {
+    let data: Vec<u8> = (0..1023u32)
+        .map(|i| (i.wrapping_mul(31) >> 2) as u8)
+        .collect();
+
+    let implementations: &[(&str, Crc16Fn)] = &[
+        ("bytewise", crc::rm_crc16_bytewise),
+        ("nibble", crc::rm_crc16_nibble),
+        #[cfg(feature = "crc16-slice4")]
+        ("slice4", crc::rm_crc16_slice4),
+        #[cfg(feature = "crc16-slice8")]
+        ("slice8", crc::rm_crc16_slice8),
+    ];
+
+    let mut group = c.benchmark_group("rm_crc16");
+    for size in SIZES {
+        group.throughput(Throughput::Bytes(size as u64));
+        for (name, crc16) in implementations {
+            group.bench_with_input(BenchmarkId::new(*name, size), &data[..size], |b, data| {
+                b.iter(|| crc16(RM_S1_CRC16_SEED, black_box(data)))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_crc16);
+criterion_main!(benches);
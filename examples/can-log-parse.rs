@@ -1,6 +1,5 @@
 use std::{io::BufRead, path::PathBuf};
 
-use candump_parse;
 use chumsky::Parser;
 
 use clap::Parser as ClapParser;
@@ -0,0 +1,188 @@
+//! Find the CRC seeds of a DUML variant from a `candump` log
+//!
+//! Frames are extracted per CAN ID from every SOF (0x55) whose length field fits in the
+//! captured stream. The header CRC8 seed is brute-forced over all 256 values. Every
+//! candidate with a valid header then votes for the single CRC16 seed that validates it,
+//! which is equivalent to trying all 65536 seeds against every frame.
+//!
+//! ```sh
+//! cargo run --example crc-seed-discovery data.log
+//! ```
+
+use std::{collections::BTreeMap, io::BufRead, path::PathBuf};
+
+use chumsky::Parser;
+use clap::Parser as ClapParser;
+use robomaster_s1_proto::crc::{rm_crc16, rm_crc8, RM_CRC16_POLY, RM_CRC8_POLY};
+
+/// Brute-force the CRC8/CRC16 seeds that validate the most frames in a CAN log
+#[derive(ClapParser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// The input file to parse.
+    /// If not provided, will read from `stdin`.
+    input: Option<PathBuf>,
+
+    /// Number of seeds to report for each CRC
+    #[arg(long, default_value_t = 3)]
+    top: usize,
+}
+
+/// Smallest frame: header, no payload, CRC16
+const MIN_FRAME_LEN: usize = 13;
+
+/// All the SOF-delimited candidate frames that fit in `stream`
+fn candidates(stream: &[u8]) -> impl Iterator<Item = &[u8]> {
+    stream
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| b == 0x55)
+        .filter_map(move |(start, _)| {
+            let header = stream.get(start..start + 3)?;
+            let len = (header[1] as usize) | ((header[2] as usize & 0b11) << 8);
+            if len < MIN_FRAME_LEN {
+                return None;
+            }
+            stream.get(start..start + len)
+        })
+}
+
+/// Undo `bits` shifts of a reflected CRC register over zero input
+///
+/// The register update is invertible because the polynomial has its top bit set.
+fn unshift16(mut crc: u16, bits: usize) -> u16 {
+    for _ in 0..bits {
+        crc = if crc & 0x8000 != 0 {
+            ((crc ^ RM_CRC16_POLY) << 1) | 1
+        } else {
+            crc << 1
+        };
+    }
+    crc
+}
+
+/// The only CRC16 seed for which `frame` is valid
+///
+/// The CRC is linear: `crc(seed, data) == crc(0, data) ^ crc(seed, zeros)`.
+fn crc16_seed(frame: &[u8]) -> u16 {
+    let (data, crc) = frame.split_at(frame.len() - 2);
+    let expected = u16::from_le_bytes([crc[0], crc[1]]);
+    unshift16(expected ^ rm_crc16(0, data), data.len() * 8)
+}
+
+fn crc8_valid(seed: u8, frame: &[u8]) -> bool {
+    rm_crc8(seed, &frame[..3]) == frame[3]
+}
+
+fn crc16_valid(seed: u16, frame: &[u8]) -> bool {
+    let (data, crc) = frame.split_at(frame.len() - 2);
+    rm_crc16(seed, data) == u16::from_le_bytes([crc[0], crc[1]])
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * count as f64 / total as f64
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let reader: Box<dyn BufRead> = if let Some(input) = args.input {
+        let file = std::fs::File::open(input).unwrap();
+
+        Box::new(std::io::BufReader::new(file))
+    } else {
+        Box::new(std::io::BufReader::new(std::io::stdin()))
+    };
+
+    // The bus is a byte stream per CAN ID
+    let mut streams: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+    let parser = candump_parse::parser();
+    for line in reader.lines() {
+        let line = if let Ok(line) = line {
+            line
+        } else {
+            continue;
+        };
+        match parser.parse(line) {
+            Ok(frame) => streams.entry(frame.id).or_default().extend(frame.data),
+            Err(e) => eprintln!("Error: {:?}", e),
+        }
+    }
+
+    let frames: Vec<&[u8]> = streams.values().flat_map(|s| candidates(s)).collect();
+    println!(
+        "{} candidate frames on {} CAN IDs (polynomials CRC8 {:#04x}, CRC16 {:#06x})",
+        frames.len(),
+        streams.len(),
+        RM_CRC8_POLY,
+        RM_CRC16_POLY
+    );
+    if frames.is_empty() {
+        return;
+    }
+
+    // Header CRC8
+    let mut crc8_counts: Vec<(u8, usize)> = (0..=255u8)
+        .map(|seed| {
+            let count = frames.iter().filter(|f| crc8_valid(seed, f)).count();
+            (seed, count)
+        })
+        .collect();
+    crc8_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    println!("CRC8 seeds:");
+    for &(seed, count) in crc8_counts.iter().take(args.top) {
+        println!(
+            "  {:#04x}: {}/{} ({:.1}%)",
+            seed,
+            count,
+            frames.len(),
+            percent(count, frames.len())
+        );
+    }
+
+    // Packet CRC16, only over the frames with a valid header
+    let crc8_seed = crc8_counts[0].0;
+    let headers: Vec<&[u8]> = frames
+        .iter()
+        .copied()
+        .filter(|f| crc8_valid(crc8_seed, f))
+        .collect();
+
+    let mut votes: BTreeMap<u16, usize> = BTreeMap::new();
+    for frame in &headers {
+        *votes.entry(crc16_seed(frame)).or_default() += 1;
+    }
+    let mut crc16_counts: Vec<(u16, usize)> = votes.into_iter().collect();
+    crc16_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    println!("CRC16 seeds (frames with CRC8 seed {:#04x}):", crc8_seed);
+    for &(seed, count) in crc16_counts.iter().take(args.top) {
+        println!(
+            "  {:#06x}: {}/{} ({:.1}%)",
+            seed,
+            count,
+            headers.len(),
+            percent(count, headers.len())
+        );
+    }
+
+    if let Some(&(crc16_seed, _)) = crc16_counts.first() {
+        let valid = headers
+            .iter()
+            .filter(|f| crc16_valid(crc16_seed, f))
+            .count();
+        println!(
+            "Result: CRC8 seed {:#04x}, CRC16 seed {:#06x}, {}/{} candidate frames valid ({:.1}%)",
+            crc8_seed,
+            crc16_seed,
+            valid,
+            frames.len(),
+            percent(valid, frames.len())
+        );
+    }
+}
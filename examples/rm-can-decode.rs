@@ -1,13 +1,21 @@
-use std::{collections::HashMap, io::BufRead, path::PathBuf};
+use std::{io::BufRead, path::PathBuf};
 
-use candump_parse;
 use chumsky::Parser;
 use robomaster_s1_proto::{
     self,
+    address::RMS1Address,
+    cipher::{FrameCipher, RepeatingXorCipher},
+    duss::vbus::{
+        cmd_view::{RMAddSubAckView, RMDelSubView, RMNodeResetView, RMVBusAckView},
+        subscription::SubscriptionTable,
+        topics::VBusUid,
+        VBusCmd,
+    },
     duss::{
         cmd_set_common::CommonCommandType, cmd_set_gimbal::GimbalCommandType,
         cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType,
     },
+    proto::StreamReassembler,
     wire::EncryptType,
 };
 
@@ -20,32 +28,145 @@ struct Args {
     /// The input file to parse.
     /// If not provided, will read from `stdin`.
     input: Option<PathBuf>,
+
+    /// Only print these topics of the pushes, as `host:id` in hex or by name, e.g. `battery`
+    #[arg(long = "topic", value_parser = parse_topic)]
+    topics: Vec<VBusUid>,
+
+    /// Key used to decrypt `XOR_ENC` payloads, in hex
+    #[arg(long, value_parser = parse_key)]
+    xor_key: Option<Box<[u8]>>,
+}
+
+fn parse_topic(s: &str) -> Result<VBusUid, String> {
+    s.parse()
+        .map_err(|_| format!("unknown topic {:?}, expected host:id or a name", s))
+}
+
+fn parse_key(s: &str) -> Result<Box<[u8]>, String> {
+    if !s.len().is_multiple_of(2) {
+        return Err("expected an even number of hex digits".to_string());
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or_else(|| format!("invalid hex key {:?}", s))
+        })
+        .collect()
 }
 
 fn show_buf<B: AsRef<[u8]>>(buf: B) -> String {
     String::from_utf8(
         buf.as_ref()
             .iter()
-            .map(|b| core::ascii::escape_default(*b))
-            .flatten()
+            .flat_map(|b| core::ascii::escape_default(*b))
             .collect(),
     )
     .unwrap()
 }
 
-fn print_packet(id: u32, packet: &[u8]) {
+/// Name of the host with the given Packet ID, or the raw ID if unknown
+fn address(pack_id: u8) -> String {
+    match RMS1Address::from_pack_id(pack_id) {
+        Some(address) => address.to_string(),
+        None => format!("{:02x}", pack_id),
+    }
+}
+
+/// Names of the topics, or the raw UIDs if unknown
+fn topic_names(topics: Option<&[VBusUid]>) -> String {
+    let Some(topics) = topics else {
+        return "<truncated>".to_string();
+    };
+    let names: Vec<String> = topics.iter().map(topic_name).collect();
+    format!("[{}]", names.join(", "))
+}
+
+/// Streams seen on the bus, used to split the pushes into topics once their ADD_SUB was acknowledged
+type Streams = SubscriptionTable<16, 16>;
+
+/// Name of the topic, or the raw UID if unknown
+fn topic_name(uid: &VBusUid) -> String {
+    match uid.info() {
+        Some(info) => info.name.to_string(),
+        None => uid.to_string(),
+    }
+}
+
+/// Payload of the frame, decrypted with `cipher` if it is encrypted
+///
+/// Encrypted payloads that cannot be decrypted are shown as is, tagged with the encrypt type.
+fn show_payload<T: AsRef<[u8]>>(
+    view: &robomaster_s1_proto::wire::RMWireFrameView<T>,
+    cipher: Option<&dyn FrameCipher>,
+) -> String {
+    if view.encrypt_type() == EncryptType::NO_ENC {
+        return format!("P, {}", show_buf(view.payload()));
+    }
+
+    let mut out = vec![0u8; view.payload().len()];
+    match cipher.map(|cipher| view.decrypt_payload_into(cipher, &mut out)) {
+        Some(Ok(payload)) => format!("D[{:?}], {}", view.encrypt_type(), show_buf(payload)),
+        Some(Err(e)) => format!(
+            "E[{:?}] ({:?}), {}",
+            view.encrypt_type(),
+            e,
+            show_buf(view.payload())
+        ),
+        None => format!("E[{:?}], {}", view.encrypt_type(), show_buf(view.payload())),
+    }
+}
+
+fn print_packet(
+    streams: &mut Streams,
+    topics: &[VBusUid],
+    cipher: Option<&dyn FrameCipher>,
+    id: u32,
+    packet: &[u8],
+) {
     let view = robomaster_s1_proto::wire::RMWireFrameView::new(packet);
     if view.is_valid() {
-        if view.cmd_set() == robomaster_s1_proto::duss::vbus::CMDSET_VBUS {
+        // Encrypted VBUS frames cannot be parsed, they are printed like unknown command sets
+        if view.cmd_set() == robomaster_s1_proto::duss::vbus::CMDSET_VBUS
+            && view.encrypt_type() == EncryptType::NO_ENC
+        {
             match view.cmd_id() {
+                cmd_id
+                    if view.is_ack()
+                        && !view.payload().is_empty()
+                        && cmd_id != robomaster_s1_proto::duss::vbus::CMDID_VBUS_PUSH_MSG =>
+                {
+                    if cmd_id == robomaster_s1_proto::duss::vbus::CMDID_VBUS_ADD_SUB {
+                        streams.handle_ack(&RMAddSubAckView::new(
+                            robomaster_s1_proto::wire::RMWireFrameView::new(packet),
+                        ));
+                    }
+                    let ack = RMVBusAckView::new(view);
+                    println!(
+                        "{:#0x}: {} → {}, VBUS ACK {:?}, RET {:#04x}, {:02x?}",
+                        id,
+                        address(ack.packet.sender_id()),
+                        address(ack.packet.receiver_id()),
+                        VBusCmd::try_from(cmd_id),
+                        ack.retcode(),
+                        ack.data()
+                    );
+                }
                 robomaster_s1_proto::duss::vbus::CMDID_VBUS_ADD_SUB => {
                     let topic_view =
                         robomaster_s1_proto::duss::vbus::topic_view::RMAddSubView::new(view);
+                    if !topic_view.packet.is_ack() {
+                        if let Err(e) = streams.insert(&topic_view) {
+                            eprintln!("{:#0x}: {:?}", id, e);
+                        }
+                    }
                     println!(
-                        "{:#0x}: {:02x} to {:02x}, VBUS Add Sub: STR {}, {}{}, {:02x?}",
+                        "{:#0x}: {} → {}, VBUS Add Sub: STR {}, {}{}, {}",
                         id,
-                        topic_view.packet.sender_id(),
-                        topic_view.packet.receiver_id(),
+                        address(topic_view.packet.sender_id()),
+                        address(topic_view.packet.receiver_id()),
                         topic_view.sub_stream_id(),
                         if topic_view.packet.need_ack() {
                             "A"
@@ -53,23 +174,45 @@ fn print_packet(id: u32, packet: &[u8]) {
                             "_"
                         },
                         if topic_view.packet.is_ack() { "K" } else { "_" },
-                        topic_view.topics()
+                        topic_names(topic_view.topics())
                     );
                 }
                 robomaster_s1_proto::duss::vbus::CMDID_VBUS_DEL_SUB => {
-                    println!("{:#0x}: VBUS Del Sub: {:02x?}", id, view.payload());
+                    let del_view = RMDelSubView::new(view);
+                    if !del_view.packet.is_ack() {
+                        streams.remove(&del_view);
+                    }
+                    println!(
+                        "{:#0x}: {} → {}, VBUS Del Sub: Node {}, STR {}, Mode {}",
+                        id,
+                        address(del_view.packet.sender_id()),
+                        address(del_view.packet.receiver_id()),
+                        address(del_view.sub_node_id()),
+                        del_view.sub_stream_id(),
+                        del_view.sub_mode()
+                    );
                 }
                 robomaster_s1_proto::duss::vbus::CMDID_VBUS_RESET_NODE => {
-                    println!("{:#0x}: VBUS Reset Node {}", id, view.receiver_id());
+                    let reset_view = RMNodeResetView::new(view);
+                    if !reset_view.packet.is_ack() {
+                        streams.remove_node(reset_view.node_id());
+                    }
+                    println!(
+                        "{:#0x}: {} → {}, VBUS Reset Node {}",
+                        id,
+                        address(reset_view.packet.sender_id()),
+                        address(reset_view.packet.receiver_id()),
+                        address(reset_view.node_id())
+                    );
                 }
                 robomaster_s1_proto::duss::vbus::CMDID_VBUS_PUSH_MSG => {
                     let topic_view = robomaster_s1_proto::duss::vbus::RMTopicView::new(view);
                     if topic_view.sub_mode() == 0 {
                         println!(
-                            "{:#0x}: {:02x} to {:02x}, #{}, VBUS PUSH Stream: {}, {}{}, DATA({}) {:02x?}",
+                            "{:#0x}: {} → {}, #{}, VBUS PUSH Stream: {}, {}{}, DATA({}) {:02x?}",
                             id,
-                            topic_view.packet.sender_id(),
-                            topic_view.packet.receiver_id(),
+                            address(topic_view.packet.sender_id()),
+                            address(topic_view.packet.receiver_id()),
                             topic_view.packet.sequence_number(),
                             topic_view.sub_id(),
                             if topic_view.packet.need_ack() {
@@ -81,12 +224,25 @@ fn print_packet(id: u32, packet: &[u8]) {
                             topic_view.data().len(),
                             topic_view.data()
                         );
+                        if let Ok(split) = streams.split(&topic_view) {
+                            for (uid, data) in split {
+                                if !topics.is_empty() && !topics.contains(&uid) {
+                                    continue;
+                                }
+                                match uid.info().and_then(|info| info.decode?(data)) {
+                                    Some(sample) => {
+                                        println!("    {}: {:?}", topic_name(&uid), sample)
+                                    }
+                                    None => println!("    {}: {:02x?}", topic_name(&uid), data),
+                                }
+                            }
+                        }
                     } else {
                         println!(
-                            "{:#0x}: {:02x} to {:02x}, VBUS PUSH ACK, {}{}, PAYLOAD {:02x?}",
+                            "{:#0x}: {} → {}, VBUS PUSH ACK, {}{}, PAYLOAD {:02x?}",
                             id,
-                            topic_view.packet.sender_id(),
-                            topic_view.packet.receiver_id(),
+                            address(topic_view.packet.sender_id()),
+                            address(topic_view.packet.receiver_id()),
                             if topic_view.packet.need_ack() {
                                 "A"
                             } else {
@@ -99,13 +255,13 @@ fn print_packet(id: u32, packet: &[u8]) {
                 }
                 _ => {
                     println!(
-                        "{:#0x}: {:02x} to {:02x}, CS {:02x}, CMD {:02x}, {}",
+                        "{:#0x}: {} → {}, CS {:02x}, CMD {:02x}, {}",
                         id,
-                        view.sender_id(),
-                        view.receiver_id(),
+                        address(view.sender_id()),
+                        address(view.receiver_id()),
                         view.cmd_set(),
                         view.cmd_id(),
-                        show_buf(view.payload())
+                        show_payload(&view, cipher)
                     );
                 }
             }
@@ -113,63 +269,58 @@ fn print_packet(id: u32, packet: &[u8]) {
             == robomaster_s1_proto::duss::cmd_set_types::CommandSetType::RM as u8
         {
             println!(
-                "{:#0x}: {:02x} to {:02x}, #{}, {}{}, CS {:?}, CMD {:?}, {}",
+                "{:#0x}: {} → {}, #{}, {}{}, CS {:?}, CMD {:?}, {}",
                 id,
-                view.sender_id(),
-                view.receiver_id(),
+                address(view.sender_id()),
+                address(view.receiver_id()),
                 view.sequence_number(),
                 if view.need_ack() { "A" } else { "_" },
                 if view.is_ack() { "K" } else { "_" },
                 CommandSetType::try_from(view.cmd_set()),
                 RMCommandType::try_from(view.cmd_id()),
-                show_buf(view.payload())
+                show_payload(&view, cipher)
             );
         } else if view.cmd_set()
             == robomaster_s1_proto::duss::cmd_set_types::CommandSetType::GIMBAL as u8
         {
             println!(
-                "{:#0x}: {:02x} to {:02x}, #{}, {}{}, CS {:?}, CMD {:?}, {}",
+                "{:#0x}: {} → {}, #{}, {}{}, CS {:?}, CMD {:?}, {}",
                 id,
-                view.sender_id(),
-                view.receiver_id(),
+                address(view.sender_id()),
+                address(view.receiver_id()),
                 view.sequence_number(),
                 if view.need_ack() { "A" } else { "_" },
                 if view.is_ack() { "K" } else { "_" },
                 CommandSetType::try_from(view.cmd_set()),
                 GimbalCommandType::try_from(view.cmd_id()),
-                show_buf(view.payload())
+                show_payload(&view, cipher)
             );
         } else if view.cmd_set()
             == robomaster_s1_proto::duss::cmd_set_types::CommandSetType::COMMON as u8
         {
             println!(
-                "{:#0x}: {:02x} to {:02x}, #{} {}{}, {}, CS {:?}, CMD {:?}, {}",
+                "{:#0x}: {} → {}, #{} {}{}, CS {:?}, CMD {:?}, {}",
                 id,
-                view.sender_id(),
-                view.receiver_id(),
+                address(view.sender_id()),
+                address(view.receiver_id()),
                 view.sequence_number(),
                 if view.need_ack() { "A" } else { "_" },
                 if view.is_ack() { "K" } else { "_" },
-                if view.encrypt_type() == EncryptType::NO_ENC {
-                    "P".to_string()
-                } else {
-                    format!("E[{:?}]", view.encrypt_type())
-                },
                 CommandSetType::try_from(view.cmd_set()),
                 CommonCommandType::try_from(view.cmd_id()),
-                show_buf(view.payload())
+                show_payload(&view, cipher)
             );
         } else {
             println!(
-                "{:#0x}: {:02x} to {:02x}, {}{}, CS {:?}, CMD {:02x}, {}",
+                "{:#0x}: {} → {}, {}{}, CS {:?}, CMD {:02x}, {}",
                 id,
-                view.sender_id(),
-                view.receiver_id(),
+                address(view.sender_id()),
+                address(view.receiver_id()),
                 if view.need_ack() { "A" } else { "_" },
                 if view.is_ack() { "K" } else { "_" },
                 CommandSetType::try_from(view.cmd_set()),
                 view.cmd_id(),
-                show_buf(view.payload())
+                show_payload(&view, cipher)
             );
         }
     } else {
@@ -180,19 +331,20 @@ fn print_packet(id: u32, packet: &[u8]) {
 fn main() {
     let args = Args::parse();
 
-    let reader: Box<dyn BufRead> = if args.input == None {
-        Box::new(std::io::BufReader::new(std::io::stdin()))
-    } else {
+    let reader: Box<dyn BufRead> = if let Some(input) = args.input {
         // Open a file reader (line-by-line)
-        let file = std::fs::File::open(&args.input.unwrap()).unwrap();
+        let file = std::fs::File::open(input).unwrap();
 
         Box::new(std::io::BufReader::new(file))
+    } else {
+        Box::new(std::io::BufReader::new(std::io::stdin()))
     };
     // Each CAN node id has a buffer
-    let mut buffers: HashMap<u32, Vec<u8>> = HashMap::new();
+    let mut reassembler = Box::new(StreamReassembler::<32, 2048>::new());
+    let mut streams = Streams::new();
+    let cipher = args.xor_key.as_deref().map(RepeatingXorCipher::new);
 
     // Parse each line
-    let mut bytes_needed: HashMap<u32, Option<usize>> = HashMap::new();
     for line in reader.lines() {
         let line = if let Ok(line) = line {
             line
@@ -204,48 +356,22 @@ fn main() {
         match result {
             Ok(frame) => {
                 let id = frame.id;
-                let buf: &mut Vec<u8> = buffers.entry(id).or_default();
 
                 // Append the data to the buffer
-                buf.extend_from_slice(&frame.data);
-
-                // eprintln!("{:0x}: buf: {:0x?}", id, buf.as_slice());
-
-                let bytes_needed_id = bytes_needed.entry(id).or_insert(None);
-                if let Some(needed) = bytes_needed_id {
-                    if Vec::len(buf) < *needed {
-                        continue;
-                    }
+                if let Err(e) = reassembler.push(id, &frame.data) {
+                    eprintln!("{:#02x}: {:?}", id, e);
+                    continue;
                 }
 
-                *bytes_needed_id = None;
-
-                // Try to parse the RM-S1 frame
-                let result = robomaster_s1_proto::proto::parse_frame(buf.as_slice());
-
-                match result {
-                    Ok((packet, consumed)) => {
-                        print_packet(id, &packet);
-                        buf.drain(0..consumed);
-                    }
-                    Err(robomaster_s1_proto::proto::ParseError::NeedMoreData(needed, consumed)) => {
-                        // eprintln!("Need more data: {}, consumed: {}", needed, consumed);
-                        buf.drain(0..consumed);
-                        *bytes_needed_id = Some(needed + Vec::len(buf));
-                    }
-                    Err(robomaster_s1_proto::proto::ParseError::NoStartOfFrame) => {
-                        eprintln!("No start of frame");
-                        // Drain the buffer
-                        buf.clear();
-                    }
-                    Err(robomaster_s1_proto::proto::ParseError::InvalidHeaderCRC(consumed)) => {
-                        eprintln!("Invalid header CRC, skipping {}", consumed);
-                        buf.drain(0..consumed);
-                    }
-                    Err(robomaster_s1_proto::proto::ParseError::InvalidPacketCRC(consumed)) => {
-                        eprintln!("{:#02x}: Invalid payload CRC, skipping {}", id, consumed);
-                        buf.drain(0..consumed);
-                    }
+                // Print all the RM-S1 frames that are complete
+                while let Some(packet) = reassembler.poll(id) {
+                    print_packet(
+                        &mut streams,
+                        &args.topics,
+                        cipher.as_ref().map(|cipher| cipher as &dyn FrameCipher),
+                        id,
+                        packet.as_bytes(),
+                    );
                 }
             }
             Err(e) => {
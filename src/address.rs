@@ -2,10 +2,12 @@ use num_enum::TryFromPrimitive;
 
 #[allow(non_camel_case_types)]
 #[repr(u16)]
-#[derive(Debug, Clone, Copy, TryFromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 pub enum RMS1Address {
     camera_id = 100,
     mobile_id = 200,
+    /// Flight controller, the VBUS master
+    fc_id = 300,
     chassis_id = 306,
     gimbal_id = 400,
     gun_id = 2300,
@@ -50,6 +52,88 @@ pub enum RMS1Address {
     robotic_arm_id = 2702,
 }
 
+impl RMS1Address {
+    /// Address of the host with the given Packet ID, if known
+    pub fn from_pack_id(pack_id: u8) -> Option<Self> {
+        Self::try_from(packid2hostid(pack_id)).ok()
+    }
+
+    pub const fn host_id(self) -> u16 {
+        self as u16
+    }
+
+    /// Packet ID (Short Address) used in the frame header
+    pub const fn pack_id(self) -> u8 {
+        hostid2packid(self as u16)
+    }
+
+    /// Host type, e.g. 24 for all the armors
+    pub const fn host_type(self) -> u8 {
+        (self as u16 / 100) as u8
+    }
+
+    /// Index of the host within its type, e.g. 3 for armor3
+    pub const fn host_index(self) -> u8 {
+        (self as u16 % 100) as u8
+    }
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            RMS1Address::camera_id => "camera",
+            RMS1Address::mobile_id => "mobile",
+            RMS1Address::fc_id => "fc",
+            RMS1Address::chassis_id => "chassis",
+            RMS1Address::gimbal_id => "gimbal",
+            RMS1Address::gun_id => "gun",
+            RMS1Address::vision_id => "vision",
+            RMS1Address::battery_id => "battery",
+            RMS1Address::hdvt_uav_id => "hdvt_uav",
+            RMS1Address::system_id => "system",
+            RMS1Address::system_scratch_id => "system_scratch",
+            RMS1Address::scratch_sys_id => "scratch_sys",
+            RMS1Address::scratch_script_id => "scratch_script",
+            RMS1Address::armor_id => "armor",
+            RMS1Address::armor1_id => "armor1",
+            RMS1Address::armor2_id => "armor2",
+            RMS1Address::armor3_id => "armor3",
+            RMS1Address::armor4_id => "armor4",
+            RMS1Address::armor5_id => "armor5",
+            RMS1Address::armor6_id => "armor6",
+            RMS1Address::esc0_id => "esc0",
+            RMS1Address::esc1_id => "esc1",
+            RMS1Address::esc2_id => "esc2",
+            RMS1Address::esc3_id => "esc3",
+            RMS1Address::blackbox_id => "blackbox",
+            RMS1Address::sensor_adapter_id => "sensor_adapter",
+            RMS1Address::sensor_adapter1_id => "sensor_adapter1",
+            RMS1Address::sensor_adapter2_id => "sensor_adapter2",
+            RMS1Address::sensor_adapter3_id => "sensor_adapter3",
+            RMS1Address::sensor_adapter4_id => "sensor_adapter4",
+            RMS1Address::sensor_adapter5_id => "sensor_adapter5",
+            RMS1Address::sensor_adapter6_id => "sensor_adapter6",
+            RMS1Address::sensor_adapter7_id => "sensor_adapter7",
+            RMS1Address::tof_id => "tof",
+            RMS1Address::tof1_id => "tof1",
+            RMS1Address::tof2_id => "tof2",
+            RMS1Address::tof3_id => "tof3",
+            RMS1Address::tof4_id => "tof4",
+            RMS1Address::servo_id => "servo",
+            RMS1Address::servo1_id => "servo1",
+            RMS1Address::servo2_id => "servo2",
+            RMS1Address::servo3_id => "servo3",
+            RMS1Address::servo4_id => "servo4",
+            RMS1Address::robotic_gripper_id => "robotic_gripper",
+            RMS1Address::robotic_arm_id => "robotic_arm",
+        }
+    }
+}
+
+impl core::fmt::Display for RMS1Address {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 /// HostID to Packet ID (Short Address)
 ///
 /// The Packet ID (Short Address) is a 1-byte value that is used to identify the sender and receiver of a packet.
@@ -62,12 +146,21 @@ pub enum RMS1Address {
 ///     host_id = ((int(host_id / 100) & 0x1f) | ((host_id % 100) << 5) & 0xe0)
 ///     return [host_id]
 /// ```
-pub fn hostid2packid(host_id: u16) -> u8 {
+pub const fn hostid2packid(host_id: u16) -> u8 {
     ((host_id / 100) & 0x1f | ((host_id % 100) << 5) & 0xe0) as u8
 }
 
+/// Packet ID (Short Address) to HostID
+///
+/// Inverse of [`hostid2packid`] for host indices below 8, which covers all the known hosts.
+pub const fn packid2hostid(pack_id: u8) -> u16 {
+    (pack_id & 0x1f) as u16 * 100 + (pack_id >> 5) as u16
+}
+
 #[cfg(test)]
 mod test {
+    extern crate std;
+
     use super::*;
 
     #[test]
@@ -123,4 +216,48 @@ mod test {
         // SENSOR_ADAPTER = 2200
         assert_eq!(hostid2packid(2200), 0x16);
     }
+
+    #[test]
+    fn test_packid2hostid() {
+        assert_eq!(packid2hostid(0xC3), 306);
+        assert_eq!(packid2hostid(0x17), 2300);
+        assert_eq!(packid2hostid(0x78), 2403);
+        assert_eq!(packid2hostid(0xF1), 1707);
+
+        for host_id in [100, 200, 306, 400, 900, 1707, 2406, 2900] {
+            assert_eq!(packid2hostid(hostid2packid(host_id)), host_id);
+        }
+    }
+
+    #[test]
+    fn test_address_from_pack_id() {
+        assert_eq!(
+            RMS1Address::from_pack_id(0x09),
+            Some(RMS1Address::hdvt_uav_id)
+        );
+        assert_eq!(
+            RMS1Address::from_pack_id(0xC3),
+            Some(RMS1Address::chassis_id)
+        );
+        assert_eq!(RMS1Address::from_pack_id(0x18), Some(RMS1Address::armor_id));
+        assert_eq!(RMS1Address::from_pack_id(0xFF), None);
+
+        let armor3 = RMS1Address::from_pack_id(0x78).unwrap();
+        assert_eq!(armor3, RMS1Address::armor3_id);
+        assert_eq!(armor3.host_type(), 24);
+        assert_eq!(armor3.host_index(), 3);
+        assert_eq!(armor3.pack_id(), 0x78);
+
+        const GUN: u8 = RMS1Address::gun_id.pack_id();
+        assert_eq!(GUN, 0x17);
+    }
+
+    #[test]
+    fn test_address_display() {
+        use std::string::ToString;
+
+        assert_eq!(RMS1Address::hdvt_uav_id.to_string(), "hdvt_uav");
+        assert_eq!(RMS1Address::chassis_id.to_string(), "chassis");
+        assert_eq!(RMS1Address::armor3_id.to_string(), "armor3");
+    }
 }
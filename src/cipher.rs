@@ -0,0 +1,157 @@
+//! Payload encryption
+//!
+//! The encrypt type of a frame is stored in the lower 3 bits of the flags byte, see [`EncryptType`].
+//! A [`FrameCipher`] encrypts or decrypts the payload in place, the length of the payload never changes.
+//!
+//! [`crate::wire::RMWireFrameView::decrypt_payload`] and [`crate::wire::RMWireFrameBuilder::build_into_with_cipher`]
+//! use a cipher to read and emit encrypted frames.
+//!
+//! The DUML XOR scheme is not implemented: no `XOR_ENC` frame has been captured on the RM-S1,
+//! so its key derivation is unknown. [`RepeatingXorCipher`] only decrypts payloads XORed with a
+//! repeating key supplied by the caller.
+
+use crate::wire::EncryptType;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CipherError {
+    /// The cipher does not implement the encrypt type
+    Unsupported(EncryptType),
+    /// The output buffer is too small
+    ///
+    /// The usize is the number of bytes needed
+    BufferTooSmall(usize),
+}
+
+/// Payload cipher
+pub trait FrameCipher {
+    fn encrypt(&self, encrypt_type: EncryptType, payload: &mut [u8]) -> Result<(), CipherError>;
+
+    fn decrypt(&self, encrypt_type: EncryptType, payload: &mut [u8]) -> Result<(), CipherError>;
+}
+
+/// Repeating-key XOR cipher for [`EncryptType::XOR_ENC`] frames
+///
+/// Each payload byte is XORed with the key, repeated over the length of the payload.
+/// Encryption and decryption are the same operation. An empty key leaves the payload unchanged.
+///
+/// This is not a reimplementation of the XOR scheme used by DJI devices: no `XOR_ENC` frame has
+/// been captured on the RM-S1 yet, so neither the key nor the way it is derived is known. The
+/// caller supplies the key.
+#[derive(Debug, Clone, Copy)]
+pub struct RepeatingXorCipher<'a> {
+    key: &'a [u8],
+}
+
+impl<'a> RepeatingXorCipher<'a> {
+    pub fn new(key: &'a [u8]) -> Self {
+        RepeatingXorCipher { key }
+    }
+
+    fn apply(&self, encrypt_type: EncryptType, payload: &mut [u8]) -> Result<(), CipherError> {
+        if encrypt_type != EncryptType::XOR_ENC {
+            return Err(CipherError::Unsupported(encrypt_type));
+        }
+
+        for (byte, key) in payload.iter_mut().zip(self.key.iter().cycle()) {
+            *byte ^= key;
+        }
+
+        Ok(())
+    }
+}
+
+impl FrameCipher for RepeatingXorCipher<'_> {
+    fn encrypt(&self, encrypt_type: EncryptType, payload: &mut [u8]) -> Result<(), CipherError> {
+        self.apply(encrypt_type, payload)
+    }
+
+    fn decrypt(&self, encrypt_type: EncryptType, payload: &mut [u8]) -> Result<(), CipherError> {
+        self.apply(encrypt_type, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wire::{BuildError, RMWireFrameBuilder};
+
+    #[test]
+    fn test_xor_round_trip() {
+        let cipher = RepeatingXorCipher::new(&[0x5A, 0xA5]);
+        let payload = b"mic:hz err";
+
+        let mut frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0x28)
+            .encrypt_type(EncryptType::XOR_ENC)
+            .payload(payload)
+            .build_with_cipher::<32>(&cipher)
+            .unwrap();
+
+        assert!(frame.is_valid());
+        assert_eq!(frame.encrypt_type(), EncryptType::XOR_ENC);
+        assert_eq!(frame.payload()[0], b'm' ^ 0x5A);
+        assert_eq!(frame.payload()[1], b'i' ^ 0xA5);
+
+        let mut out = [0u8; 16];
+        let decrypted = frame.decrypt_payload_into(&cipher, &mut out).unwrap();
+        assert_eq!(decrypted, payload);
+
+        frame.decrypt_payload(&cipher).unwrap();
+        assert!(frame.is_valid());
+        assert_eq!(frame.encrypt_type(), EncryptType::NO_ENC);
+        assert_eq!(frame.payload(), payload);
+    }
+
+    #[test]
+    fn test_unsupported() {
+        let cipher = RepeatingXorCipher::new(&[0x5A]);
+
+        let result = RMWireFrameBuilder::new()
+            .receiver_id(0x28)
+            .encrypt_type(EncryptType::AES_128_ENC)
+            .payload(&[0x00])
+            .build_with_cipher::<32>(&cipher);
+        assert_eq!(
+            result.err(),
+            Some(BuildError::Cipher(CipherError::Unsupported(
+                EncryptType::AES_128_ENC
+            )))
+        );
+
+        let frame = RMWireFrameBuilder::new()
+            .receiver_id(0x28)
+            .encrypt_type(EncryptType::XOR_ENC)
+            .payload(&[0x00, 0x01])
+            .build_with_cipher::<32>(&cipher)
+            .unwrap();
+        let mut out = [0u8; 1];
+        assert_eq!(
+            frame.decrypt_payload_into(&cipher, &mut out),
+            Err(CipherError::BufferTooSmall(2))
+        );
+    }
+
+    #[test]
+    fn test_missing_cipher() {
+        let builder = RMWireFrameBuilder::new()
+            .receiver_id(0x28)
+            .encrypt_type(EncryptType::XOR_ENC)
+            .payload(&[0x00, 0x01]);
+
+        assert_eq!(
+            builder.build::<32>().err(),
+            Some(BuildError::MissingCipher(EncryptType::XOR_ENC))
+        );
+
+        let mut buf = [0u8; 32];
+        assert_eq!(
+            builder.build_into(&mut buf).err(),
+            Some(BuildError::MissingCipher(EncryptType::XOR_ENC))
+        );
+        assert_eq!(
+            builder.build_into_with(&mut buf, 2, |_| ()).err(),
+            Some(BuildError::MissingCipher(EncryptType::XOR_ENC))
+        );
+    }
+}
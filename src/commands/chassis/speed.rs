@@ -1,6 +1,6 @@
 use crate::{
     duss::{cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType},
-    wire::RMWireFrameView,
+    wire::{FrameError, RMWireFrameView},
 };
 
 /// Chassis speed vector control packet
@@ -20,6 +20,23 @@ impl<T: AsRef<[u8]>> SpeedSetView<T> {
         Self { packet }
     }
 
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<Self, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(Self { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(CommandSetType::RM as u8, RMCommandType::SPEED_SET as u8)?;
+        if packet.payload().len() != 12 {
+            return Err(FrameError::InvalidPayloadLength(packet.payload().len()));
+        }
+
+        Ok(())
+    }
+
     pub fn x(&self) -> f32 {
         f32::from_le_bytes([
             self.packet.payload()[0],
@@ -69,6 +86,51 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> SpeedSetView<T> {
     }
 }
 
+/// Chassis speed mode packet (SET_CHASSIS_SPEED)
+///
+/// Sent by RoboStack to switch the chassis between its slow, normal and fast modes.
+/// RoboStack sends `0x03` to enter the slow mode, the other values have not been captured.
+#[derive(Debug)]
+pub struct ChassisSpeedModeSetView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> ChassisSpeedModeSetView<T> {
+    pub fn new(packet: RMWireFrameView<T>) -> Self {
+        Self { packet }
+    }
+
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<Self, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(Self { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(
+            CommandSetType::RM as u8,
+            RMCommandType::SET_CHASSIS_SPEED as u8,
+        )?;
+        if packet.payload().len() != 1 {
+            return Err(FrameError::InvalidPayloadLength(packet.payload().len()));
+        }
+
+        Ok(())
+    }
+
+    pub fn mode(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+}
+
+impl<T: AsRef<[u8]> + AsMut<[u8]>> ChassisSpeedModeSetView<T> {
+    pub fn set_mode(&mut self, mode: u8) {
+        self.packet.payload_mut()[0] = mode;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     extern crate std;
@@ -103,9 +165,62 @@ mod tests {
         assert_eq!(speed_vector_control.y(), 1.0);
         assert_eq!(speed_vector_control.omega(), 1.0);
 
-        assert_eq!(speed_vector_control.is_valid(), true);
+        assert!(speed_vector_control.is_valid());
 
         speed_vector_control.set_x(2.0);
         assert_eq!(speed_vector_control.x(), 2.0);
     }
+
+    #[test]
+    fn test_speed_set_try_new() {
+        let buf = [
+            0x55, 0x19, 0x04, 0xe4, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x3F, 0x21, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x7d, 0x16,
+        ];
+
+        let speed = SpeedSetView::try_new(RMWireFrameView::new(&buf)).unwrap();
+        assert_eq!(speed.omega(), 1.0);
+
+        assert_eq!(
+            SpeedSetView::try_new(RMWireFrameView::new(&buf[..20])).err(),
+            Some(FrameError::LengthMismatch(0x19, 20))
+        );
+
+        let buf = [
+            0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+        ];
+        assert_eq!(
+            SpeedSetView::try_new(RMWireFrameView::new(&buf)).err(),
+            Some(FrameError::WrongCommand(0x48, 0x08))
+        );
+    }
+
+    #[test]
+    fn test_speed_mode_set() {
+        let mut buf = [0u8; 16];
+        let packet = crate::wire::RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0xC3)
+            .cmd_set(CommandSetType::RM as u8)
+            .cmd_id(RMCommandType::SET_CHASSIS_SPEED as u8)
+            .payload(&[0x03])
+            .build_into(&mut buf)
+            .unwrap();
+
+        let mut speed_mode = ChassisSpeedModeSetView::try_new(packet).unwrap();
+        assert_eq!(speed_mode.mode(), 0x03);
+        speed_mode.set_mode(0x01);
+        assert_eq!(speed_mode.mode(), 0x01);
+
+        let buf = [
+            0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+        ];
+        assert_eq!(
+            ChassisSpeedModeSetView::try_new(RMWireFrameView::new(&buf)).err(),
+            Some(FrameError::WrongCommand(0x48, 0x08))
+        );
+    }
 }
@@ -1,8 +1,12 @@
 //! The RM-S1 Work Mode Set command
 
-use crate::wire::RMWireFrameView;
+use crate::{
+    duss::{cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType},
+    wire::{FrameError, RMWireFrameView},
+};
 
 /// The RM-S1 Work Mode Set command
+#[derive(Debug)]
 pub struct ChassisWorkModeSetView<T: AsRef<[u8]>> {
     pub packet: RMWireFrameView<T>,
 }
@@ -12,6 +16,23 @@ impl<T: AsRef<[u8]>> ChassisWorkModeSetView<T> {
         Self { packet }
     }
 
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<Self, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(Self { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(CommandSetType::RM as u8, RMCommandType::WORK_MODE_SET as u8)?;
+        if packet.payload().is_empty() {
+            return Err(FrameError::InvalidPayloadLength(0));
+        }
+
+        Ok(())
+    }
+
     pub fn work_mode(&self) -> u8 {
         self.packet.payload()[0]
     }
@@ -29,7 +50,7 @@ mod tests {
     use super::*;
     use crate::{
         duss::{cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType},
-        wire::RMWireFrameView,
+        wire::{RMWireFrameBuilder, RMWireFrameView},
     };
 
     #[test]
@@ -48,4 +69,36 @@ mod tests {
         );
         assert_eq!(work_mode_set.work_mode(), 0x01); // SDK Mode
     }
+
+    #[test]
+    fn test_workmodesetview_try_new() {
+        // The capture of `test_workmodesetview` is truncated
+        let capture = [
+            0x55, 0x1B, 0x04, 0x75, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x3F, 0x19, 0x01, 0x04, 0x20,
+        ];
+        assert!(ChassisWorkModeSetView::try_new(RMWireFrameView::new(&capture)).is_err());
+
+        let mut buf = [0u8; 32];
+        let packet = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0xC3)
+            .cmd_set(CommandSetType::RM as u8)
+            .cmd_id(RMCommandType::WORK_MODE_SET as u8)
+            .payload(&[0x01])
+            .build_into(&mut buf)
+            .unwrap();
+
+        let work_mode_set = ChassisWorkModeSetView::try_new(packet).unwrap();
+        assert_eq!(work_mode_set.work_mode(), 0x01);
+
+        let packet = RMWireFrameBuilder::new()
+            .cmd_set(CommandSetType::RM as u8)
+            .cmd_id(RMCommandType::WORK_MODE_SET as u8)
+            .build_into(&mut buf)
+            .unwrap();
+        assert_eq!(
+            ChassisWorkModeSetView::try_new(packet).err(),
+            Some(FrameError::InvalidPayloadLength(0))
+        );
+    }
 }
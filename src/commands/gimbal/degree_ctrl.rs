@@ -2,7 +2,7 @@
 
 use crate::{
     duss::{cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType},
-    wire::RMWireFrameView,
+    wire::{FrameError, RMWireFrameView},
 };
 use num_enum::TryFromPrimitive;
 
@@ -38,6 +38,26 @@ impl<T: AsRef<[u8]>> GimbalSetDegreeView<T> {
         Self { packet }
     }
 
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<Self, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(Self { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(
+            CommandSetType::RM as u8,
+            RMCommandType::GIMBAL_DEGREE_SET as u8,
+        )?;
+        if packet.payload().len() != 17 {
+            return Err(FrameError::InvalidPayloadLength(packet.payload().len()));
+        }
+
+        Ok(())
+    }
+
     pub fn task_id(&self) -> u8 {
         self.packet.payload()[0]
     }
@@ -131,3 +151,63 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> GimbalSetDegreeView<T> {
         &mut self.packet
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::wire::RMWireFrameBuilder;
+
+    #[test]
+    fn test_degree_set_try_new() {
+        let mut payload = [0u8; 17];
+        payload[3..5].copy_from_slice(&(-300i16).to_le_bytes());
+        payload[7..9].copy_from_slice(&150i16.to_le_bytes());
+        payload[11..13].copy_from_slice(&540u16.to_le_bytes());
+
+        let mut buf = [0u8; 32];
+        let packet = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0x04)
+            .cmd_set(CommandSetType::RM as u8)
+            .cmd_id(RMCommandType::GIMBAL_DEGREE_SET as u8)
+            .payload(&payload)
+            .build_into(&mut buf)
+            .unwrap();
+
+        let degree_set = GimbalSetDegreeView::try_new(packet).unwrap();
+        assert_eq!(degree_set.yaw_degree(), -300);
+        assert_eq!(degree_set.pitch_degree(), 150);
+        assert_eq!(degree_set.yaw_accel(), 540);
+        assert!(degree_set.is_valid());
+    }
+
+    #[test]
+    fn test_degree_set_try_new_short_payload() {
+        let mut buf = [0u8; 32];
+        let packet = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0x04)
+            .cmd_set(CommandSetType::RM as u8)
+            .cmd_id(RMCommandType::GIMBAL_DEGREE_SET as u8)
+            .payload(&[0u8; 16])
+            .build_into(&mut buf)
+            .unwrap();
+
+        assert_eq!(
+            GimbalSetDegreeView::try_new(packet).err(),
+            Some(FrameError::InvalidPayloadLength(16))
+        );
+    }
+
+    #[test]
+    fn test_degree_set_try_new_wrong_command() {
+        let buf = [
+            0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+        ];
+        assert_eq!(
+            GimbalSetDegreeView::try_new(RMWireFrameView::new(&buf)).err(),
+            Some(FrameError::WrongCommand(0x48, 0x08))
+        );
+    }
+}
@@ -1,6 +1,9 @@
 //! The RM-S1 Gimbal Work Mode Set command
 
-use crate::wire::RMWireFrameView;
+use crate::{
+    duss::{cmd_set_gimbal::GimbalCommandType, cmd_set_types::CommandSetType},
+    wire::{FrameError, RMWireFrameView},
+};
 use num_enum::TryFromPrimitive;
 
 /// The RM-S1 Gimbal Work Mode
@@ -14,6 +17,7 @@ pub enum GimbalMode {
 }
 
 /// The RM-S1 Gimbal Work Mode Set command
+#[derive(Debug)]
 pub struct GimbalWorkModeSetView<T: AsRef<[u8]>> {
     pub packet: RMWireFrameView<T>,
 }
@@ -23,6 +27,26 @@ impl<T: AsRef<[u8]>> GimbalWorkModeSetView<T> {
         Self { packet }
     }
 
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<Self, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(Self { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(
+            CommandSetType::GIMBAL as u8,
+            GimbalCommandType::GIMBAL_SET_MODE as u8,
+        )?;
+        if packet.payload().len() != 2 {
+            return Err(FrameError::InvalidPayloadLength(packet.payload().len()));
+        }
+
+        Ok(())
+    }
+
     pub fn mode(&self) -> GimbalMode {
         self.packet.payload()[0]
             .try_into()
@@ -75,6 +99,21 @@ mod tests {
         assert_eq!(work_mode_set.mode(), GimbalMode::Free); // Free Mode
         assert_eq!(work_mode_set.cmd(), 0x00); // Always 0x00
 
-        assert_eq!(work_mode_set.is_valid(), true);
+        assert!(work_mode_set.is_valid());
+    }
+
+    #[test]
+    fn test_workmodesetview_try_new() {
+        let buf = [
+            0x55, 0x0F, 0x04, 0xa2, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x04, 0x4C, 0x00, 0x00, 0x6c,
+            0xe1,
+        ];
+
+        let work_mode_set = GimbalWorkModeSetView::try_new(RMWireFrameView::new(&buf)).unwrap();
+        assert_eq!(work_mode_set.mode(), GimbalMode::Free);
+        assert_eq!(
+            GimbalWorkModeSetView::try_new(RMWireFrameView::new(&buf[..14])).err(),
+            Some(FrameError::LengthMismatch(0x0F, 14))
+        );
     }
 }
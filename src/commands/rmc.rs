@@ -2,9 +2,13 @@
 //!
 //! NOTE: This is a work in progress and may not be accurate
 
-use crate::wire::RMWireFrameView;
+use crate::{
+    duss::{cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType},
+    wire::{FrameError, RMWireFrameView},
+};
 
 /// The RM-S1 RMC message format
+#[derive(Debug)]
 pub struct RMCPacketView<T: AsRef<[u8]>> {
     pub packet: RMWireFrameView<T>,
 }
@@ -13,6 +17,19 @@ impl<T: AsRef<[u8]>> RMCPacketView<T> {
     pub fn new(packet: RMWireFrameView<T>) -> Self {
         Self { packet }
     }
+
+    /// Create a view after checking the frame and the command
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<Self, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(Self { packet })
+    }
+
+    /// Check the command, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(CommandSetType::RM as u8, RMCommandType::FC_RMC as u8)?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -34,11 +51,25 @@ mod tests {
         let packet = RMWireFrameView::new(&buf);
         let rmc_packet = RMCPacketView::new(packet);
 
-        assert_eq!(rmc_packet.packet.is_valid(), true);
+        assert!(rmc_packet.packet.is_valid());
 
         assert_eq!(rmc_packet.packet.cmd_set(), CommandSetType::RM as u8);
         assert_eq!(rmc_packet.packet.cmd_id(), RMCommandType::FC_RMC as u8);
 
         std::println!("{:0x?}", rmc_packet.packet);
     }
+
+    #[test]
+    fn test_rmcpacketview_try_new() {
+        let buf = [
+            0x55, 0x1B, 0x04, 0x75, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x3F, 0x60, 0x00, 0x04, 0x20,
+            0x00, 0x01, 0x00, 0x40, 0x00, 0x02, 0x10, 0x04, 0x03, 0x00, 0x04, 0xFA, 0xF0,
+        ];
+
+        assert!(RMCPacketView::try_new(RMWireFrameView::new(&buf)).is_ok());
+        assert_eq!(
+            RMCPacketView::try_new(RMWireFrameView::new(&buf[..26])).err(),
+            Some(FrameError::LengthMismatch(0x1B, 26))
+        );
+    }
 }
@@ -0,0 +1,637 @@
+//! VBUS client
+//!
+//! [`VBusClient`] registers a node with the VBUS master (the FC, 0x03) and subscribes to a
+//! list of streams, like RoboStack does at startup:
+//!
+//! 1. ADD_NODE for our node, answered by an ACK from the FC
+//! 2. ADD_MSG for every stream, one at a time, each answered by an ACK
+//! 3. Pushes of the streams, split into topics with a [`SubscriptionTable`]
+//!
+//! RoboStack sends ADD_NODE a second time after its first stream, before the others. Set
+//! [`VBusClientConfig::re_add_node`] to follow that order.
+//!
+//! Requests are sent with `NEED_ACK_AFTER_EXEC` and retransmitted by a [`TransactionTable`].
+//! When a request is rejected the client waits for one timeout before sending it again.
+//! The handshake starts over when a request times out after all retransmissions, when the FC
+//! resets our node, or when nothing was received from the FC for
+//! [`VBusClientConfig::silence_timeout_ms`].
+//!
+//! The client does not perform any IO: the caller feeds it the received frames with
+//! [`VBusClient::handle_frame`] and sends the frames returned by [`VBusClient::poll_transmit`],
+//! passing the current time in milliseconds like for [`TransactionTable`].
+//!
+//! # Example
+//!
+//! ```
+//! use robomaster_s1_proto::duss::vbus::{
+//!     client::{VBusClient, VBusClientConfig},
+//!     topic_view::RMAddSubBuilder,
+//!     topics::{VBusUid, VBUS_BATTERY_STATS},
+//! };
+//!
+//! const TOPICS: [VBusUid; 1] = [VBusUid { uid: VBUS_BATTERY_STATS }];
+//! const STREAMS: [RMAddSubBuilder; 1] = [RMAddSubBuilder::new(0x09, 0)
+//!     .topics(&TOPICS)
+//!     .frequency(1)];
+//!
+//! let mut client = VBusClient::<4, 8>::new(VBusClientConfig::new(0x09, &STREAMS));
+//!
+//! let mut buf = [0u8; 128];
+//! let add_node = client.poll_transmit(0, &mut buf).unwrap().unwrap();
+//! assert_eq!(add_node.cmd_id(), 0x01);
+//!
+//! // Nothing else is sent until the FC acknowledges
+//! assert!(client.poll_transmit(10, &mut buf).unwrap().is_none());
+//! ```
+
+use crate::{
+    address::RMS1Address,
+    transaction::{RetransmitConfig, TransactionError, TransactionEvent, TransactionTable},
+    wire::{BuildError, RMWireFrameBuilder, RMWireFrameView},
+};
+
+use super::{
+    cmd_view::{RMAddNodeView, RMAddSubAckView, RMNodeResetView, RMVBusAckView, VBUS_NODE_VERSION},
+    subscription::{SubscriptionError, SubscriptionTable, TopicSplit},
+    topic_view::{RMAddSubBuilder, RMTopicView},
+    CMDID_VBUS_ADD_NODE, CMDID_VBUS_ADD_SUB, CMDID_VBUS_PUSH_MSG, CMDID_VBUS_RESET_NODE,
+    CMDSET_VBUS,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClientError {
+    /// The request could not be built
+    Build(BuildError),
+    /// The request could not be registered for retransmission
+    Transaction(TransactionError),
+    /// The stream does not fit in the subscription table
+    Subscription(SubscriptionError),
+}
+
+/// Settings of a [`VBusClient`]
+#[derive(Debug, Clone)]
+pub struct VBusClientConfig<'a> {
+    /// Packet ID of our node, e.g. 0x09 for RoboStack
+    pub node_id: u8,
+    /// Packet ID of the VBUS master, the FC by default
+    pub master_id: u8,
+    /// Streams to subscribe to, their node ID must be `node_id`
+    pub streams: &'a [RMAddSubBuilder<'a>],
+    /// Send ADD_NODE again after the first stream, as RoboStack does
+    pub re_add_node: bool,
+    pub retransmit: RetransmitConfig,
+    /// Time without any frame from the master after which the handshake starts over, in milliseconds
+    ///
+    /// Three periods of the slowest stream by default, and at least one second.
+    pub silence_timeout_ms: u32,
+}
+
+impl<'a> VBusClientConfig<'a> {
+    pub const fn new(node_id: u8, streams: &'a [RMAddSubBuilder<'a>]) -> Self {
+        VBusClientConfig {
+            node_id,
+            master_id: RMS1Address::fc_id.pack_id(),
+            streams,
+            re_add_node: false,
+            retransmit: RetransmitConfig {
+                timeout_ms: 100,
+                max_retries: 3,
+            },
+            silence_timeout_ms: silence_timeout_ms(streams),
+        }
+    }
+}
+
+/// Three periods of the slowest of `streams`, at least one second
+const fn silence_timeout_ms(streams: &[RMAddSubBuilder<'_>]) -> u32 {
+    let mut timeout_ms = 1000;
+    let mut i = 0;
+    while i < streams.len() {
+        if let Some(period_ms) = streams[i].period_ms() {
+            if 3 * period_ms > timeout_ms {
+                timeout_ms = 3 * period_ms;
+            }
+        }
+        i += 1;
+    }
+    timeout_ms
+}
+
+/// Progress of the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientState {
+    /// Registering the node
+    AddingNode,
+    /// Subscribing to the stream at this index of [`VBusClientConfig::streams`]
+    Subscribing(usize),
+    /// Registering the node again after the first stream, see [`VBusClientConfig::re_add_node`]
+    ReAddingNode,
+    /// All the streams are subscribed
+    Running,
+}
+
+/// Events reported by [`VBusClient::handle_frame`]
+#[derive(Debug)]
+pub enum ClientEvent<'a, const TOPICS: usize> {
+    /// The master accepted the node
+    NodeAdded,
+    /// The master accepted the subscription of the stream
+    Subscribed(u8),
+    /// The master rejected the request with CMD_ID, return code
+    Rejected(u8, u8),
+    /// The master reset our node, the handshake starts over
+    Reset,
+    /// Topics of a push
+    Push(TopicSplit<'a, TOPICS>),
+    /// A push that could not be split
+    InvalidPush(SubscriptionError),
+}
+
+/// Sans-IO VBUS client
+///
+/// Tracks up to `STREAMS` streams of up to `TOPICS` topics each.
+pub struct VBusClient<'a, const STREAMS: usize, const TOPICS: usize> {
+    config: VBusClientConfig<'a>,
+    state: ClientState,
+    transactions: TransactionTable<1, 1>,
+    streams: SubscriptionTable<STREAMS, TOPICS>,
+    /// Time of the last frame from the master
+    last_rx: Option<u32>,
+    /// Time of the last rejected request
+    rejected_at: Option<u32>,
+}
+
+impl<'a, const STREAMS: usize, const TOPICS: usize> VBusClient<'a, STREAMS, TOPICS> {
+    pub const fn new(config: VBusClientConfig<'a>) -> Self {
+        let retransmit = config.retransmit;
+        VBusClient {
+            config,
+            state: ClientState::AddingNode,
+            transactions: TransactionTable::new(retransmit),
+            streams: SubscriptionTable::new(),
+            last_rx: None,
+            rejected_at: None,
+        }
+    }
+
+    pub fn config(&self) -> &VBusClientConfig<'a> {
+        &self.config
+    }
+
+    pub fn state(&self) -> ClientState {
+        self.state
+    }
+
+    /// Streams requested so far, confirmed once the master accepted them
+    pub fn streams(&self) -> &SubscriptionTable<STREAMS, TOPICS> {
+        &self.streams
+    }
+
+    /// Start the handshake over
+    pub fn restart(&mut self) {
+        self.state = ClientState::AddingNode;
+        self.transactions.clear();
+        self.streams.clear();
+        self.last_rx = None;
+        self.rejected_at = None;
+    }
+
+    /// Frame to send at `now`, if any, written to the start of `buf`
+    ///
+    /// Call until it returns `None`.
+    pub fn poll_transmit<'b>(
+        &mut self,
+        now: u32,
+        buf: &'b mut [u8],
+    ) -> Result<Option<RMWireFrameView<&'b mut [u8]>>, ClientError> {
+        if self.state == ClientState::Running {
+            let silent = match self.last_rx {
+                Some(last_rx) => now.wrapping_sub(last_rx) >= self.config.silence_timeout_ms,
+                None => false,
+            };
+            if !silent {
+                return Ok(None);
+            }
+            self.restart();
+        }
+
+        match self.transactions.poll(now) {
+            Some(TransactionEvent::Retransmit(transaction)) => {
+                return self
+                    .build_request(buf, transaction.sequence_number)
+                    .map(Some);
+            }
+            Some(TransactionEvent::TimedOut(_)) => self.restart(),
+            None if !self.transactions.is_empty() => return Ok(None),
+            None => {}
+        }
+
+        if let Some(rejected_at) = self.rejected_at {
+            if now.wrapping_sub(rejected_at) < self.config.retransmit.timeout_ms {
+                return Ok(None);
+            }
+            self.rejected_at = None;
+        }
+
+        let sequence_number = self
+            .transactions
+            .next_sequence(self.config.node_id, self.config.master_id)
+            .map_err(ClientError::Transaction)?;
+        let frame = self.build_request(buf, sequence_number)?;
+        self.transactions
+            .register(&frame, now)
+            .map_err(ClientError::Transaction)?;
+
+        Ok(Some(frame))
+    }
+
+    /// Process a frame received at `now`
+    pub fn handle_frame<'f, T: AsRef<[u8]>>(
+        &'f mut self,
+        frame: &'f RMWireFrameView<T>,
+        now: u32,
+    ) -> Option<ClientEvent<'f, TOPICS>> {
+        if frame.sender_id() != self.config.master_id {
+            return None;
+        }
+        self.last_rx = Some(now);
+
+        if frame.cmd_set() != CMDSET_VBUS {
+            return None;
+        }
+
+        if frame.is_ack() {
+            self.transactions.handle_ack(frame)?;
+            return self.handle_ack(frame, now);
+        }
+
+        match frame.cmd_id() {
+            CMDID_VBUS_RESET_NODE => {
+                let reset =
+                    RMNodeResetView::try_new(RMWireFrameView::new(frame.as_bytes())).ok()?;
+                if reset.node_id() != self.config.node_id {
+                    return None;
+                }
+                self.restart();
+                self.last_rx = Some(now);
+                Some(ClientEvent::Reset)
+            }
+            CMDID_VBUS_PUSH_MSG if frame.receiver_id() == self.config.node_id => {
+                let push = RMTopicView::try_new(RMWireFrameView::new(frame.as_bytes())).ok()?;
+                if push.sub_mode() != 0 {
+                    return None;
+                }
+                let (stream_id, data) = (push.sub_id(), &frame.payload()[2..]);
+                match self.streams.split_data(
+                    self.config.master_id,
+                    self.config.node_id,
+                    stream_id,
+                    data,
+                ) {
+                    Ok(split) => Some(ClientEvent::Push(split)),
+                    Err(e) => Some(ClientEvent::InvalidPush(e)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Process the ACK of our outstanding request
+    fn handle_ack<T: AsRef<[u8]>>(
+        &mut self,
+        frame: &RMWireFrameView<T>,
+        now: u32,
+    ) -> Option<ClientEvent<'static, TOPICS>> {
+        let ack = RMVBusAckView::try_new(RMWireFrameView::new(frame.as_bytes())).ok()?;
+        if frame.cmd_id() == CMDID_VBUS_ADD_SUB {
+            self.streams
+                .handle_ack(&RMAddSubAckView::new(RMWireFrameView::new(
+                    frame.as_bytes(),
+                )));
+        }
+        if !ack.is_ok() {
+            self.rejected_at = Some(now);
+            return Some(ClientEvent::Rejected(frame.cmd_id(), ack.retcode()));
+        }
+
+        match (self.state, frame.cmd_id()) {
+            (ClientState::AddingNode, CMDID_VBUS_ADD_NODE) => {
+                self.advance(0);
+                Some(ClientEvent::NodeAdded)
+            }
+            (ClientState::ReAddingNode, CMDID_VBUS_ADD_NODE) => {
+                self.advance(1);
+                Some(ClientEvent::NodeAdded)
+            }
+            (ClientState::Subscribing(index), CMDID_VBUS_ADD_SUB) => {
+                let stream_id = self.config.streams[index].stream_id();
+                if index == 0 && self.config.re_add_node {
+                    self.state = ClientState::ReAddingNode;
+                } else {
+                    self.advance(index + 1);
+                }
+                Some(ClientEvent::Subscribed(stream_id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Move on to the stream at `index`, or to running after the last one
+    fn advance(&mut self, index: usize) {
+        self.state = if index < self.config.streams.len() {
+            ClientState::Subscribing(index)
+        } else {
+            ClientState::Running
+        };
+    }
+
+    /// Build the request of the current state
+    fn build_request<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+        sequence_number: u16,
+    ) -> Result<RMWireFrameView<&'b mut [u8]>, ClientError> {
+        let header = RMWireFrameBuilder::new()
+            .sender_id(self.config.node_id)
+            .receiver_id(self.config.master_id)
+            .sequence_number(sequence_number)
+            .need_ack_after_exec(true);
+
+        match self.state {
+            ClientState::AddingNode | ClientState::ReAddingNode => {
+                RMAddNodeView::build_into(&header, buf, self.config.node_id, VBUS_NODE_VERSION)
+                    .map(|view| view.packet)
+                    .map_err(ClientError::Build)
+            }
+            ClientState::Subscribing(index) => {
+                let view = self.config.streams[index]
+                    .build_into(&header, buf)
+                    .map_err(ClientError::Build)?;
+                self.streams
+                    .insert(&view)
+                    .map_err(ClientError::Subscription)?;
+                Ok(view.packet)
+            }
+            ClientState::Running => unreachable!("no request while running"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        duss::vbus::{
+            cmd_view::RMAddNodeAckView,
+            topic_data::TopicSample,
+            topics::{VBusUid, VBUS_BATTERY_STATS, VBUS_CHASSIS_VELOCITY, VBUS_TURRET_YAW},
+        },
+        frames::{
+            ROBOSTACK_ADD_NODE, ROBOSTACK_ADD_NODE_AGAIN, ROBOSTACK_ADD_SUB_BATTERY,
+            ROBOSTACK_ADD_SUB_TURRET,
+        },
+        wire::RMWireFrameBuf,
+    };
+
+    const TOPICS: [VBusUid; 1] = [VBusUid {
+        uid: VBUS_BATTERY_STATS,
+    }];
+    const STREAMS: [RMAddSubBuilder; 1] = [RMAddSubBuilder::new(0x09, 0)
+        .timestamp_requested(true)
+        .stop_when_disconnected(true)
+        .topics(&TOPICS)
+        .frequency(1)];
+
+    fn fc_header(sequence_number: u16) -> RMWireFrameBuilder<'static> {
+        RMWireFrameBuilder::new()
+            .sender_id(0x03)
+            .receiver_id(0x09)
+            .sequence_number(sequence_number)
+    }
+
+    fn fc_ack(
+        sequence_number: u16,
+        cmd_id: u8,
+        payload: &[u8],
+    ) -> RMWireFrameView<RMWireFrameBuf<32>> {
+        fc_header(sequence_number)
+            .is_ack(true)
+            .cmd_set(CMDSET_VBUS)
+            .cmd_id(cmd_id)
+            .payload(payload)
+            .build()
+            .unwrap()
+    }
+
+    fn battery_push() -> RMWireFrameView<RMWireFrameBuf<32>> {
+        fc_header(0)
+            .cmd_set(CMDSET_VBUS)
+            .cmd_id(CMDID_VBUS_PUSH_MSG)
+            .payload(&[
+                0x00, 0x00, 0xac, 0x2e, 0xdd, 0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00,
+            ])
+            .build()
+            .unwrap()
+    }
+
+    /// Run the handshake from `now`, returns the time at which it completed
+    fn connect(client: &mut VBusClient<'_, 2, 4>, now: u32) -> u32 {
+        let mut buf = [0u8; 64];
+
+        let frame = client.poll_transmit(now, &mut buf).unwrap().unwrap();
+        let add_node = RMAddNodeView::try_new(frame).unwrap();
+        assert_eq!(add_node.node_id(), 0x09);
+        assert_eq!(add_node.version(), VBUS_NODE_VERSION);
+        assert!(add_node.packet.need_ack_after_exec());
+        let ack = fc_ack(
+            add_node.packet.sequence_number(),
+            CMDID_VBUS_ADD_NODE,
+            &[0x00, 0x03],
+        );
+        assert!(RMAddNodeAckView::try_new(RMWireFrameView::new(ack.as_bytes())).is_ok());
+        assert!(matches!(
+            client.handle_frame(&ack, now + 1),
+            Some(ClientEvent::NodeAdded)
+        ));
+
+        let frame = client.poll_transmit(now + 1, &mut buf).unwrap().unwrap();
+        assert_eq!(frame.cmd_id(), CMDID_VBUS_ADD_SUB);
+        assert_eq!(
+            frame.payload()[..5],
+            [0x09, 0x00, 0x03, 0x00, 0x01],
+            "ADD_SUB as in the RoboStack capture"
+        );
+        assert!(!client.streams().get(0x03, 0x09, 0).unwrap().confirmed);
+        let ack = fc_ack(
+            frame.sequence_number(),
+            CMDID_VBUS_ADD_SUB,
+            &[0x00, 0x03, 0x00, 0x00],
+        );
+        assert!(RMAddSubAckView::try_new(RMWireFrameView::new(ack.as_bytes())).is_ok());
+        assert!(matches!(
+            client.handle_frame(&ack, now + 2),
+            Some(ClientEvent::Subscribed(0))
+        ));
+        assert_eq!(client.state(), ClientState::Running);
+        assert!(client.poll_transmit(now + 2, &mut buf).unwrap().is_none());
+
+        now + 2
+    }
+
+    #[test]
+    fn test_handshake_and_push() {
+        let mut client = VBusClient::<2, 4>::new(VBusClientConfig::new(0x09, &STREAMS));
+        let now = connect(&mut client, 0);
+        assert!(client.streams().get(0x03, 0x09, 0).unwrap().confirmed);
+
+        let push = battery_push();
+        match client.handle_frame(&push, now) {
+            Some(ClientEvent::Push(split)) => {
+                let mut samples = split.samples();
+                match samples.next() {
+                    Some((uid, Some(TopicSample::Battery(battery)))) => {
+                        assert_eq!(uid.uid, VBUS_BATTERY_STATS);
+                        assert_eq!(battery.voltage_mv(), 11948);
+                    }
+                    other => panic!("unexpected sample {:?}", other),
+                }
+                assert!(samples.next().is_none());
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_retransmit_and_reject() {
+        let mut client = VBusClient::<2, 4>::new(VBusClientConfig::new(0x09, &STREAMS));
+        let mut buf = [0u8; 64];
+
+        let sequence_number = client
+            .poll_transmit(0, &mut buf)
+            .unwrap()
+            .unwrap()
+            .sequence_number();
+        assert!(client.poll_transmit(50, &mut buf).unwrap().is_none());
+
+        // Retransmitted with the same sequence number
+        let frame = client.poll_transmit(100, &mut buf).unwrap().unwrap();
+        assert_eq!(frame.cmd_id(), CMDID_VBUS_ADD_NODE);
+        assert_eq!(frame.sequence_number(), sequence_number);
+
+        // Rejected, sent again after one timeout
+        let ack = fc_ack(sequence_number, CMDID_VBUS_ADD_NODE, &[0x01]);
+        assert!(matches!(
+            client.handle_frame(&ack, 110),
+            Some(ClientEvent::Rejected(CMDID_VBUS_ADD_NODE, 0x01))
+        ));
+        assert!(client.poll_transmit(150, &mut buf).unwrap().is_none());
+        let frame = client.poll_transmit(210, &mut buf).unwrap().unwrap();
+        assert_eq!(frame.cmd_id(), CMDID_VBUS_ADD_NODE);
+        assert_ne!(frame.sequence_number(), sequence_number);
+        assert_eq!(client.state(), ClientState::AddingNode);
+    }
+
+    #[test]
+    fn test_resubscribe() {
+        let mut client = VBusClient::<2, 4>::new(VBusClientConfig::new(0x09, &STREAMS));
+        let mut buf = [0u8; 64];
+
+        // Silence from the FC for three periods of the 1 Hz stream
+        let now = connect(&mut client, 0);
+        assert!(client
+            .poll_transmit(now + 2999, &mut buf)
+            .unwrap()
+            .is_none());
+        let frame = client.poll_transmit(now + 3000, &mut buf).unwrap().unwrap();
+        assert_eq!(frame.cmd_id(), CMDID_VBUS_ADD_NODE);
+        assert_eq!(client.state(), ClientState::AddingNode);
+        assert!(client.streams().is_empty());
+
+        // Node reset by the FC
+        let mut client = VBusClient::<2, 4>::new(VBusClientConfig::new(0x09, &STREAMS));
+        let now = connect(&mut client, 0);
+        let mut reset_buf = [0u8; 32];
+        let reset = RMNodeResetView::build_into(&fc_header(7), &mut reset_buf, 0x09).unwrap();
+        assert!(matches!(
+            client.handle_frame(&reset.packet, now),
+            Some(ClientEvent::Reset)
+        ));
+        assert_eq!(client.state(), ClientState::AddingNode);
+        connect(&mut client, now);
+    }
+
+    #[test]
+    fn test_silence_timeout() {
+        let config = VBusClientConfig::new(0x09, &STREAMS);
+        assert_eq!(config.master_id, 0x03);
+        assert_eq!(config.silence_timeout_ms, 3000);
+        const FAST: [RMAddSubBuilder; 1] = [RMAddSubBuilder::new(0x09, 0).frequency(50)];
+        assert_eq!(VBusClientConfig::new(0x09, &FAST).silence_timeout_ms, 1000);
+
+        // Pushes of the 1 Hz stream on time keep the client running
+        let mut client = VBusClient::<2, 4>::new(config);
+        let mut buf = [0u8; 64];
+        let mut now = connect(&mut client, 0);
+        for _ in 0..5 {
+            now += 1000;
+            assert!(client.poll_transmit(now, &mut buf).unwrap().is_none());
+            assert!(matches!(
+                client.handle_frame(&battery_push(), now),
+                Some(ClientEvent::Push(_))
+            ));
+        }
+        assert_eq!(client.state(), ClientState::Running);
+    }
+
+    #[test]
+    fn test_robostack_order() {
+        const TURRET: [VBusUid; 2] = [
+            VBusUid {
+                uid: VBUS_TURRET_YAW,
+            },
+            VBusUid {
+                uid: VBUS_CHASSIS_VELOCITY,
+            },
+        ];
+        const ROBOSTACK: [RMAddSubBuilder; 2] = [
+            RMAddSubBuilder::new(0x09, 0)
+                .timestamp_requested(true)
+                .stop_when_disconnected(true)
+                .topics(&TOPICS)
+                .frequency(1),
+            RMAddSubBuilder::new(0x09, 1)
+                .timestamp_requested(true)
+                .stop_when_disconnected(true)
+                .topics(&TURRET)
+                .frequency(50),
+        ];
+        let mut config = VBusClientConfig::new(0x09, &ROBOSTACK);
+        config.re_add_node = true;
+        let mut client = VBusClient::<2, 4>::new(config);
+        let mut buf = [0u8; 64];
+
+        // Same requests as the RoboStack capture, apart from the sequence numbers
+        let captures: [&[u8]; 4] = [
+            &ROBOSTACK_ADD_NODE,
+            &ROBOSTACK_ADD_SUB_BATTERY,
+            &ROBOSTACK_ADD_NODE_AGAIN,
+            &ROBOSTACK_ADD_SUB_TURRET,
+        ];
+        for (now, capture) in (0..).zip(captures) {
+            let capture = RMWireFrameView::new(capture);
+            let frame = client.poll_transmit(now, &mut buf).unwrap().unwrap();
+            assert_eq!(frame.cmd_id(), capture.cmd_id());
+            assert_eq!(frame.payload(), capture.payload());
+
+            let payload: &[u8] = match frame.cmd_id() {
+                CMDID_VBUS_ADD_NODE => &[0x00, 0x03],
+                _ => &[0x00, 0x03, 0x00, 0x00],
+            };
+            let ack = fc_ack(frame.sequence_number(), frame.cmd_id(), payload);
+            assert!(client.handle_frame(&ack, now).is_some());
+        }
+
+        assert_eq!(client.state(), ClientState::Running);
+        assert!(client.streams().get(0x03, 0x09, 1).unwrap().confirmed);
+    }
+}
@@ -0,0 +1,852 @@
+//! Views of the VBUS node and subscription management commands
+//!
+//! Every request is answered by an ACK whose payload starts with a return code, 0 on success.
+//!
+//! The layouts of ADD_NODE, NODE_RESET, ADD_MSG and DEL_MSG match the RoboStack captures and
+//! the RoboMaster SDK.
+//!
+//! # Experimental
+//!
+//! QUERY_CONF, SET_PUSH_FREQ and PUSH_CTRL have never been captured and the SDK does not send
+//! them. [`RMQueryConfView`], [`RMSetPushFreqView`] and [`RMPushCtrlView`] use a guessed layout
+//! starting with the node ID and the stream ID, and may change once a capture is available.
+//! [`crate::message::decode`] does not return them.
+
+use crate::wire::{BuildError, FrameError, RMWireFrameBuilder, RMWireFrameView};
+
+use super::{
+    CMDID_VBUS_ADD_NODE, CMDID_VBUS_ADD_SUB, CMDID_VBUS_DEL_SUB, CMDID_VBUS_PUSH_CTRL,
+    CMDID_VBUS_QUERY_CONF, CMDID_VBUS_RESET_NODE, CMDID_VBUS_SET_PUSH_FREQ, CMDSET_VBUS,
+};
+
+/// VBUS protocol version sent in ADD_NODE by RoboStack
+pub const VBUS_NODE_VERSION: u32 = 0x0300_0000;
+
+/// Check the command of a VBUS frame and its payload length, the CRCs are not checked
+fn check_vbus<T: AsRef<[u8]>>(
+    packet: &RMWireFrameView<T>,
+    cmd_id: u8,
+    payload_len: usize,
+) -> Result<(), FrameError> {
+    packet.check_command(CMDSET_VBUS, cmd_id)?;
+    if packet.payload().len() < payload_len {
+        return Err(FrameError::InvalidPayloadLength(packet.payload().len()));
+    }
+    Ok(())
+}
+
+/// Build a VBUS command frame from the header fields of `header`
+fn build<'b>(
+    header: &RMWireFrameBuilder<'_>,
+    buf: &'b mut [u8],
+    cmd_id: u8,
+    is_ack: bool,
+    payload_len: usize,
+    fill: impl FnOnce(&mut [u8]),
+) -> Result<RMWireFrameView<&'b mut [u8]>, BuildError> {
+    header
+        .clone()
+        .cmd_set(CMDSET_VBUS)
+        .cmd_id(cmd_id)
+        .is_ack(is_ack)
+        .build_into_with(buf, payload_len, fill)
+}
+
+/// Node Add Packet
+///
+/// Registers a node with the VBUS master before it subscribes to topics.
+#[derive(Debug)]
+pub struct RMAddNodeView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMAddNodeView<T> {
+    pub const PAYLOAD_LEN: usize = 5;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMAddNodeView<T> {
+        RMAddNodeView { packet }
+    }
+
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMAddNodeView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMAddNodeView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_ADD_NODE, Self::PAYLOAD_LEN)?;
+        Ok(())
+    }
+
+    pub fn node_id(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    pub fn version(&self) -> u32 {
+        let payload = self.packet.payload();
+        u32::from_le_bytes([payload[1], payload[2], payload[3], payload[4]])
+    }
+}
+
+/// Mutable Methods
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMAddNodeView<T> {
+    pub fn set_node_id(&mut self, node_id: u8) {
+        self.packet.payload_mut()[0] = node_id;
+    }
+
+    pub fn set_version(&mut self, version: u32) {
+        self.packet.payload_mut()[1..5].copy_from_slice(&version.to_le_bytes());
+    }
+}
+
+impl<'b> RMAddNodeView<&'b mut [u8]> {
+    /// Write an ADD_NODE request to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        node_id: u8,
+        version: u32,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_ADD_NODE,
+            false,
+            Self::PAYLOAD_LEN,
+            |payload| {
+                payload[0] = node_id;
+                payload[1..5].copy_from_slice(&version.to_le_bytes());
+            },
+        )?;
+        Ok(RMAddNodeView { packet })
+    }
+}
+
+/// Node Reset Packet
+///
+/// Drops the node and all its subscriptions. Also sent by the VBUS master, e.g. after a
+/// restart, in which case the node has to register and subscribe again.
+#[derive(Debug)]
+pub struct RMNodeResetView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMNodeResetView<T> {
+    pub const PAYLOAD_LEN: usize = 1;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMNodeResetView<T> {
+        RMNodeResetView { packet }
+    }
+
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMNodeResetView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMNodeResetView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_RESET_NODE, Self::PAYLOAD_LEN)?;
+        Ok(())
+    }
+
+    pub fn node_id(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+}
+
+/// Mutable Methods
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMNodeResetView<T> {
+    pub fn set_node_id(&mut self, node_id: u8) {
+        self.packet.payload_mut()[0] = node_id;
+    }
+}
+
+impl<'b> RMNodeResetView<&'b mut [u8]> {
+    /// Write a NODE_RESET request to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        node_id: u8,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_RESET_NODE,
+            false,
+            Self::PAYLOAD_LEN,
+            |payload| payload[0] = node_id,
+        )?;
+        Ok(RMNodeResetView { packet })
+    }
+}
+
+/// Subscriber Delete Packet
+///
+/// Removes the subscription of a stream.
+#[derive(Debug)]
+pub struct RMDelSubView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMDelSubView<T> {
+    pub const PAYLOAD_LEN: usize = 3;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMDelSubView<T> {
+        RMDelSubView { packet }
+    }
+
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMDelSubView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMDelSubView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_DEL_SUB, Self::PAYLOAD_LEN)?;
+        Ok(())
+    }
+
+    pub fn sub_mode(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    pub fn sub_node_id(&self) -> u8 {
+        self.packet.payload()[1]
+    }
+
+    pub fn sub_stream_id(&self) -> u8 {
+        self.packet.payload()[2]
+    }
+}
+
+/// Mutable Methods
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMDelSubView<T> {
+    pub fn set_sub_mode(&mut self, sub_mode: u8) {
+        self.packet.payload_mut()[0] = sub_mode;
+    }
+
+    pub fn set_sub_node_id(&mut self, node_id: u8) {
+        self.packet.payload_mut()[1] = node_id;
+    }
+
+    pub fn set_sub_stream_id(&mut self, stream_id: u8) {
+        self.packet.payload_mut()[2] = stream_id;
+    }
+}
+
+impl<'b> RMDelSubView<&'b mut [u8]> {
+    /// Write a DEL_MSG request to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        sub_mode: u8,
+        node_id: u8,
+        stream_id: u8,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_DEL_SUB,
+            false,
+            Self::PAYLOAD_LEN,
+            |payload| payload.copy_from_slice(&[sub_mode, node_id, stream_id]),
+        )?;
+        Ok(RMDelSubView { packet })
+    }
+}
+
+/// Query Configuration Packet
+///
+/// Asks for the configuration of a stream.
+///
+/// Experimental: the layout is guessed, see the [module documentation](self#experimental).
+#[derive(Debug)]
+pub struct RMQueryConfView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMQueryConfView<T> {
+    pub const PAYLOAD_LEN: usize = 2;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMQueryConfView<T> {
+        RMQueryConfView { packet }
+    }
+
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMQueryConfView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMQueryConfView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_QUERY_CONF, Self::PAYLOAD_LEN)?;
+        Ok(())
+    }
+
+    pub fn sub_node_id(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    pub fn sub_stream_id(&self) -> u8 {
+        self.packet.payload()[1]
+    }
+}
+
+/// Mutable Methods
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMQueryConfView<T> {
+    pub fn set_sub_node_id(&mut self, node_id: u8) {
+        self.packet.payload_mut()[0] = node_id;
+    }
+
+    pub fn set_sub_stream_id(&mut self, stream_id: u8) {
+        self.packet.payload_mut()[1] = stream_id;
+    }
+}
+
+impl<'b> RMQueryConfView<&'b mut [u8]> {
+    /// Write a QUERY_CONF request to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        node_id: u8,
+        stream_id: u8,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_QUERY_CONF,
+            false,
+            Self::PAYLOAD_LEN,
+            |payload| payload.copy_from_slice(&[node_id, stream_id]),
+        )?;
+        Ok(RMQueryConfView { packet })
+    }
+}
+
+/// Set Push Frequency Packet
+///
+/// Changes the push rate of a stream without subscribing again.
+///
+/// Experimental: the layout is guessed, see the [module documentation](self#experimental).
+#[derive(Debug)]
+pub struct RMSetPushFreqView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMSetPushFreqView<T> {
+    pub const PAYLOAD_LEN: usize = 4;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMSetPushFreqView<T> {
+        RMSetPushFreqView { packet }
+    }
+
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMSetPushFreqView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMSetPushFreqView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_SET_PUSH_FREQ, Self::PAYLOAD_LEN)?;
+        Ok(())
+    }
+
+    pub fn sub_node_id(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    pub fn sub_stream_id(&self) -> u8 {
+        self.packet.payload()[1]
+    }
+
+    pub fn frequency(&self) -> u16 {
+        let payload = self.packet.payload();
+        u16::from_le_bytes([payload[2], payload[3]])
+    }
+}
+
+/// Mutable Methods
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMSetPushFreqView<T> {
+    pub fn set_sub_node_id(&mut self, node_id: u8) {
+        self.packet.payload_mut()[0] = node_id;
+    }
+
+    pub fn set_sub_stream_id(&mut self, stream_id: u8) {
+        self.packet.payload_mut()[1] = stream_id;
+    }
+
+    pub fn set_frequency(&mut self, frequency: u16) {
+        self.packet.payload_mut()[2..4].copy_from_slice(&frequency.to_le_bytes());
+    }
+}
+
+impl<'b> RMSetPushFreqView<&'b mut [u8]> {
+    /// Write a SET_PUSH_FREQ request to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        node_id: u8,
+        stream_id: u8,
+        frequency: u16,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_SET_PUSH_FREQ,
+            false,
+            Self::PAYLOAD_LEN,
+            |payload| {
+                payload[0] = node_id;
+                payload[1] = stream_id;
+                payload[2..4].copy_from_slice(&frequency.to_le_bytes());
+            },
+        )?;
+        Ok(RMSetPushFreqView { packet })
+    }
+}
+
+/// Push Control Packet
+///
+/// Pauses or resumes the pushes of a stream, the subscription is kept.
+///
+/// Experimental: the layout is guessed, see the [module documentation](self#experimental).
+#[derive(Debug)]
+pub struct RMPushCtrlView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMPushCtrlView<T> {
+    pub const PAYLOAD_LEN: usize = 3;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMPushCtrlView<T> {
+        RMPushCtrlView { packet }
+    }
+
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMPushCtrlView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMPushCtrlView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_PUSH_CTRL, Self::PAYLOAD_LEN)?;
+        Ok(())
+    }
+
+    pub fn sub_node_id(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    pub fn sub_stream_id(&self) -> u8 {
+        self.packet.payload()[1]
+    }
+
+    /// `false` pauses the stream, `true` resumes it
+    pub fn enabled(&self) -> bool {
+        self.packet.payload()[2] != 0
+    }
+}
+
+/// Mutable Methods
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMPushCtrlView<T> {
+    pub fn set_sub_node_id(&mut self, node_id: u8) {
+        self.packet.payload_mut()[0] = node_id;
+    }
+
+    pub fn set_sub_stream_id(&mut self, stream_id: u8) {
+        self.packet.payload_mut()[1] = stream_id;
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.packet.payload_mut()[2] = enabled as u8;
+    }
+}
+
+impl<'b> RMPushCtrlView<&'b mut [u8]> {
+    /// Write a PUSH_CTRL request to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        node_id: u8,
+        stream_id: u8,
+        enabled: bool,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_PUSH_CTRL,
+            false,
+            Self::PAYLOAD_LEN,
+            |payload| payload.copy_from_slice(&[node_id, stream_id, enabled as u8]),
+        )?;
+        Ok(RMPushCtrlView { packet })
+    }
+}
+
+/// ACK of a VBUS request
+///
+/// The payload starts with the return code, followed by command specific data.
+#[derive(Debug)]
+pub struct RMVBusAckView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMVBusAckView<T> {
+    pub fn new(packet: RMWireFrameView<T>) -> RMVBusAckView<T> {
+        RMVBusAckView { packet }
+    }
+
+    /// Create a view after checking the frame, the ACK flag, the command set and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMVBusAckView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMVBusAckView { packet })
+    }
+
+    /// Check the ACK flag, the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, packet.cmd_id(), 1)?;
+        if !packet.is_ack() {
+            return Err(FrameError::NotAck);
+        }
+
+        Ok(())
+    }
+
+    pub fn retcode(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    /// The request succeeded
+    pub fn is_ok(&self) -> bool {
+        self.retcode() == 0
+    }
+
+    /// Command specific data after the return code
+    pub fn data(&self) -> &[u8] {
+        &self.packet.payload()[1..]
+    }
+}
+
+/// Mutable Methods
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMVBusAckView<T> {
+    pub fn set_retcode(&mut self, retcode: u8) {
+        self.packet.payload_mut()[0] = retcode;
+    }
+}
+
+impl<'b> RMVBusAckView<&'b mut [u8]> {
+    /// Write an ACK carrying only a return code to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        cmd_id: u8,
+        retcode: u8,
+    ) -> Result<Self, BuildError> {
+        let packet = build(header, buf, cmd_id, true, 1, |payload| payload[0] = retcode)?;
+        Ok(RMVBusAckView { packet })
+    }
+}
+
+/// ACK of ADD_NODE
+#[derive(Debug)]
+pub struct RMAddNodeAckView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMAddNodeAckView<T> {
+    pub const PAYLOAD_LEN: usize = 2;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMAddNodeAckView<T> {
+        RMAddNodeAckView { packet }
+    }
+
+    /// Create a view after checking the frame, the ACK flag, the command and the payload length
+    ///
+    /// Failed requests may be answered with the return code only.
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMAddNodeAckView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMAddNodeAckView { packet })
+    }
+
+    /// Check the ACK flag, the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_ADD_NODE, 1)?;
+        if !packet.is_ack() {
+            return Err(FrameError::NotAck);
+        }
+
+        Ok(())
+    }
+
+    pub fn retcode(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.retcode() == 0
+    }
+
+    /// Node ID of the publisher, if the request succeeded
+    pub fn pub_node_id(&self) -> Option<u8> {
+        self.packet.payload().get(1).copied()
+    }
+}
+
+impl<'b> RMAddNodeAckView<&'b mut [u8]> {
+    /// Write a successful ADD_NODE ACK to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        pub_node_id: u8,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_ADD_NODE,
+            true,
+            Self::PAYLOAD_LEN,
+            |payload| payload.copy_from_slice(&[0, pub_node_id]),
+        )?;
+        Ok(RMAddNodeAckView { packet })
+    }
+}
+
+/// ACK of ADD_MSG (subscriber add)
+#[derive(Debug)]
+pub struct RMAddSubAckView<T: AsRef<[u8]>> {
+    pub packet: RMWireFrameView<T>,
+}
+
+impl<T: AsRef<[u8]>> RMAddSubAckView<T> {
+    pub const PAYLOAD_LEN: usize = 4;
+
+    pub fn new(packet: RMWireFrameView<T>) -> RMAddSubAckView<T> {
+        RMAddSubAckView { packet }
+    }
+
+    /// Create a view after checking the frame, the ACK flag, the command and the payload length
+    ///
+    /// Failed requests may be answered with the return code only.
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMAddSubAckView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMAddSubAckView { packet })
+    }
+
+    /// Check the ACK flag, the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        check_vbus(packet, CMDID_VBUS_ADD_SUB, 1)?;
+        if !packet.is_ack() {
+            return Err(FrameError::NotAck);
+        }
+
+        Ok(())
+    }
+
+    pub fn retcode(&self) -> u8 {
+        self.packet.payload()[0]
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.retcode() == 0
+    }
+
+    pub fn pub_node_id(&self) -> Option<u8> {
+        self.packet.payload().get(1).copied()
+    }
+
+    pub fn sub_mode(&self) -> Option<u8> {
+        self.packet.payload().get(2).copied()
+    }
+
+    /// Stream ID of the accepted subscription
+    pub fn sub_stream_id(&self) -> Option<u8> {
+        self.packet.payload().get(3).copied()
+    }
+}
+
+impl<'b> RMAddSubAckView<&'b mut [u8]> {
+    /// Write a successful ADD_MSG ACK to the start of `buf`
+    pub fn build_into(
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+        pub_node_id: u8,
+        sub_mode: u8,
+        stream_id: u8,
+    ) -> Result<Self, BuildError> {
+        let packet = build(
+            header,
+            buf,
+            CMDID_VBUS_ADD_SUB,
+            true,
+            Self::PAYLOAD_LEN,
+            |payload| payload.copy_from_slice(&[0, pub_node_id, sub_mode, stream_id]),
+        )?;
+        Ok(RMAddSubAckView { packet })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn header() -> RMWireFrameBuilder<'static> {
+        RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .sequence_number(0x0001)
+            .need_ack_after_exec(true)
+    }
+
+    #[test]
+    fn test_add_node() {
+        // RoboStack init capture, CRCs blanked out
+        let capture = [
+            0x55, 0x12, 0x04, 0xFF, 0x09, 0x03, 0x01, 0x00, 0x40, 0x48, 0x01, 0x09, 0x00, 0x00,
+            0x00, 0x03, 0xFF, 0xFF,
+        ];
+        let view = RMAddNodeView::new(RMWireFrameView::new(&capture));
+        assert_eq!(view.node_id(), 0x09);
+        assert_eq!(view.version(), VBUS_NODE_VERSION);
+
+        let mut buf = [0u8; 32];
+        let mut built =
+            RMAddNodeView::build_into(&header(), &mut buf, 0x09, VBUS_NODE_VERSION).unwrap();
+        assert!(RMAddNodeView::try_new(RMWireFrameView::new(built.packet.as_bytes())).is_ok());
+        assert_eq!(built.packet.as_bytes()[4..16], capture[4..16]);
+
+        built.set_node_id(0x0A);
+        built.set_version(0x0200_0000);
+        assert_eq!(built.node_id(), 0x0A);
+        assert_eq!(built.version(), 0x0200_0000);
+    }
+
+    #[test]
+    fn test_requests_round_trip() {
+        let mut buf = [0u8; 32];
+
+        let view = RMNodeResetView::build_into(&header(), &mut buf, 0x09).unwrap();
+        let view = RMNodeResetView::try_new(RMWireFrameView::new(view.packet.as_bytes())).unwrap();
+        assert_eq!(view.node_id(), 0x09);
+        assert_eq!(view.packet.receiver_id(), 0x03);
+
+        let view = RMDelSubView::build_into(&header(), &mut buf, 0, 0x09, 2).unwrap();
+        let view = RMDelSubView::try_new(RMWireFrameView::new(view.packet.as_bytes())).unwrap();
+        assert_eq!(view.packet.payload(), &[0x00, 0x09, 0x02]);
+        assert_eq!(view.sub_mode(), 0);
+        assert_eq!(view.sub_node_id(), 0x09);
+        assert_eq!(view.sub_stream_id(), 2);
+
+        let view = RMQueryConfView::build_into(&header(), &mut buf, 0x09, 1).unwrap();
+        let view = RMQueryConfView::try_new(RMWireFrameView::new(view.packet.as_bytes())).unwrap();
+        assert_eq!(view.sub_node_id(), 0x09);
+        assert_eq!(view.sub_stream_id(), 1);
+
+        let view = RMSetPushFreqView::build_into(&header(), &mut buf, 0x09, 1, 10).unwrap();
+        let view =
+            RMSetPushFreqView::try_new(RMWireFrameView::new(view.packet.as_bytes())).unwrap();
+        assert_eq!(view.packet.payload(), &[0x09, 0x01, 0x0A, 0x00]);
+        assert_eq!(view.frequency(), 10);
+
+        let view = RMPushCtrlView::build_into(&header(), &mut buf, 0x09, 1, false).unwrap();
+        let view = RMPushCtrlView::try_new(RMWireFrameView::new(view.packet.as_bytes())).unwrap();
+        assert_eq!(view.sub_stream_id(), 1);
+        assert!(!view.enabled());
+    }
+
+    #[test]
+    fn test_mutators() {
+        let mut buf = [0u8; 32];
+
+        let mut view = RMSetPushFreqView::build_into(&header(), &mut buf, 0x09, 1, 10).unwrap();
+        view.set_frequency(50);
+        view.set_sub_stream_id(3);
+        view.set_sub_node_id(0x0A);
+        assert_eq!(view.packet.payload(), &[0x0A, 0x03, 0x32, 0x00]);
+
+        let mut view = RMPushCtrlView::build_into(&header(), &mut buf, 0x09, 1, false).unwrap();
+        view.set_enabled(true);
+        assert!(view.enabled());
+
+        let mut view = RMDelSubView::build_into(&header(), &mut buf, 0, 0x09, 2).unwrap();
+        view.set_sub_mode(1);
+        view.set_sub_node_id(0x0A);
+        view.set_sub_stream_id(4);
+        assert_eq!(view.packet.payload(), &[0x01, 0x0A, 0x04]);
+    }
+
+    #[test]
+    fn test_acks() {
+        let mut buf = [0u8; 32];
+        let ack_header = RMWireFrameBuilder::new()
+            .sender_id(0x03)
+            .receiver_id(0x09)
+            .sequence_number(0x0002);
+
+        let ack = RMAddSubAckView::build_into(&ack_header, &mut buf, 0x03, 0, 1).unwrap();
+        assert!(ack.packet.is_valid());
+        assert!(ack.packet.is_ack());
+        assert!(ack.is_ok());
+        assert_eq!(ack.pub_node_id(), Some(0x03));
+        assert_eq!(ack.sub_stream_id(), Some(1));
+
+        let ack = RMAddNodeAckView::build_into(&ack_header, &mut buf, 0x03).unwrap();
+        assert!(ack.is_ok());
+        assert_eq!(ack.pub_node_id(), Some(0x03));
+
+        let mut ack =
+            RMVBusAckView::build_into(&ack_header, &mut buf, CMDID_VBUS_DEL_SUB, 0).unwrap();
+        assert!(ack.is_ok());
+        assert!(ack.data().is_empty());
+        ack.set_retcode(0x22);
+        assert!(!ack.is_ok());
+
+        // The CRC has to be updated after a mutation
+        let crc16 = ack.packet.crc16_computed();
+        ack.packet.set_packet_crc16_field(crc16);
+
+        let ack = RMAddSubAckView::try_new(RMWireFrameView::new(ack.packet.as_bytes()));
+        assert_eq!(
+            ack.err(),
+            Some(FrameError::WrongCommand(CMDSET_VBUS, CMDID_VBUS_DEL_SUB))
+        );
+
+        // A request is not an ACK, even with a valid payload
+        let request = RMDelSubView::build_into(&header(), &mut buf, 0, 0x09, 2).unwrap();
+        let request = RMWireFrameView::new(request.packet.as_bytes());
+        assert_eq!(
+            RMVBusAckView::try_new(request).err(),
+            Some(FrameError::NotAck)
+        );
+    }
+
+    #[test]
+    fn test_payload_too_short() {
+        let mut buf = [0u8; 32];
+        let frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .cmd_set(CMDSET_VBUS)
+            .cmd_id(CMDID_VBUS_SET_PUSH_FREQ)
+            .payload(&[0x09, 0x01])
+            .build_into(&mut buf)
+            .unwrap();
+
+        assert_eq!(
+            RMSetPushFreqView::try_new(frame).err(),
+            Some(FrameError::InvalidPayloadLength(2))
+        );
+    }
+}
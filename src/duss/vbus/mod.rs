@@ -3,15 +3,21 @@
 //! This module contains the definitions of the VBUS protocol packets that are used in the RM-S1 protocol.
 
 pub const CMDSET_VBUS: u8 = 0x48;
+pub const CMDID_VBUS_ADD_NODE: u8 = 0x01;
 pub const CMDID_VBUS_RESET_NODE: u8 = 0x02;
 pub const CMDID_VBUS_ADD_SUB: u8 = 0x03;
 pub const CMDID_VBUS_DEL_SUB: u8 = 0x04;
+pub const CMDID_VBUS_QUERY_CONF: u8 = 0x05;
+pub const CMDID_VBUS_SET_PUSH_FREQ: u8 = 0x06;
+pub const CMDID_VBUS_PUSH_CTRL: u8 = 0x07;
 pub const CMDID_VBUS_PUSH_MSG: u8 = 0x08;
 
+use num_enum::TryFromPrimitive;
+
 /// The VBUS protocol command set
 #[allow(non_camel_case_types)]
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, TryFromPrimitive)]
 pub enum VBusCmd {
     ADD_NODE = 0x01,
     NODE_RESET = 0x02,
@@ -23,6 +29,10 @@ pub enum VBusCmd {
     DATA_ANALYSIS = 0x08,
 }
 
+pub mod client;
+pub mod cmd_view;
+pub mod subscription;
+pub mod topic_data;
 pub mod topic_view;
 pub mod topics;
 
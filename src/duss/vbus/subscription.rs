@@ -0,0 +1,651 @@
+//! Splitting pushes into topics
+//!
+//! A push (`CMDID_VBUS_PUSH_MSG`) only carries the stream ID of the subscription and the
+//! data of all its topics back to back, in the order of the ADD_SUB request that created the
+//! stream. [`SubscriptionTable`] records the topic list of every stream from the ADD_SUB
+//! requests seen on the bus or sent by us, and splits the pushes using the topic sizes of the
+//! catalog in [`super::topics`].
+//!
+//! A stream is identified by its publisher (the receiver of the ADD_SUB, the sender of the
+//! pushes), the node that subscribed (the receiver of the pushes) and its stream ID. It stays
+//! pending until the publisher accepts the ADD_SUB, and is dropped if the publisher rejects it:
+//! only accepted streams split pushes. A repeated ADD_SUB leaves the accepted stream in place
+//! until the publisher accepts it in turn. One topic of unknown size per stream is allowed, it gets
+//! the bytes left over by the others.
+//!
+//! # Example
+//!
+//! ```
+//! use robomaster_s1_proto::{
+//!     duss::vbus::{
+//!         cmd_view::RMAddSubAckView,
+//!         subscription::SubscriptionTable,
+//!         topic_view::{RMAddSubBuilder, RMTopicView},
+//!         topics::{VBusUid, VBUS_BATTERY_STATS},
+//!     },
+//!     wire::{RMWireFrameBuilder, RMWireFrameView},
+//! };
+//!
+//! let topics = [VBusUid { uid: VBUS_BATTERY_STATS }];
+//! let header = RMWireFrameBuilder::new()
+//!     .sender_id(0x09)
+//!     .receiver_id(0x03)
+//!     .sequence_number(2)
+//!     .need_ack(true);
+//! let mut buf = [0u8; 64];
+//! let add_sub = RMAddSubBuilder::new(0x09, 0)
+//!     .topics(&topics)
+//!     .build_into(&header, &mut buf)
+//!     .unwrap();
+//!
+//! let mut table = SubscriptionTable::<4, 8>::new();
+//! table.insert(&add_sub).unwrap();
+//!
+//! // Accepted by the FC
+//! let header = RMWireFrameBuilder::new()
+//!     .sender_id(0x03)
+//!     .receiver_id(0x09)
+//!     .sequence_number(2);
+//! let mut buf = [0u8; 32];
+//! let ack = RMAddSubAckView::build_into(&header, &mut buf, 0x03, 0, 0).unwrap();
+//! assert!(table.handle_ack(&ack).unwrap().confirmed);
+//!
+//! let push = RMWireFrameBuilder::new()
+//!     .sender_id(0x03)
+//!     .receiver_id(0x09)
+//!     .cmd_set(0x48)
+//!     .cmd_id(0x08)
+//!     .payload(&[0x00, 0x00, 0xac, 0x2e, 0xdd, 0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00])
+//!     .build::<32>()
+//!     .unwrap();
+//! let push = RMTopicView::new(RMWireFrameView::new(push.as_bytes()));
+//!
+//! let mut split = table.split(&push).unwrap();
+//! let (uid, data) = split.next().unwrap();
+//! assert_eq!(uid.uid, VBUS_BATTERY_STATS);
+//! assert_eq!(data.len(), 10);
+//! assert!(split.next().is_none());
+//! ```
+
+use super::{
+    cmd_view::{RMAddSubAckView, RMDelSubView},
+    topic_data::TopicSample,
+    topic_view::{RMAddSubView, RMTopicView},
+    topics::{lookup, VBusUid},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SubscriptionError {
+    /// All slots for streams are taken
+    TableFull,
+    /// The subscription has more topics than a slot holds
+    TooManyTopics(usize),
+    /// The ADD_SUB payload does not hold the announced topics
+    InvalidTopics,
+    /// No accepted subscription for the (publisher, node, stream) of the push
+    UnknownStream(u8, u8, u8),
+    /// More than one topic of the stream has an unknown size
+    UnknownSizes,
+    /// The push data does not match the topic sizes (expected, actual)
+    LengthMismatch(usize, usize),
+}
+
+/// Topics of a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subscription<const TOPICS: usize> {
+    /// Node sending the pushes, the receiver of the ADD_SUB
+    pub publisher_id: u8,
+    /// Node receiving the pushes
+    pub node_id: u8,
+    pub stream_id: u8,
+    pub frequency: u16,
+    /// Sequence number of the ADD_SUB, repeated by its ACK
+    pub sequence_number: u16,
+    /// The publisher accepted the ADD_SUB
+    pub confirmed: bool,
+    topics: [VBusUid; TOPICS],
+    num_topics: usize,
+}
+
+impl<const TOPICS: usize> Subscription<TOPICS> {
+    pub fn topics(&self) -> &[VBusUid] {
+        &self.topics[..self.num_topics]
+    }
+
+    /// Size of every topic in the push data of `data_len` bytes
+    fn sizes(&self, data_len: usize) -> Result<[usize; TOPICS], SubscriptionError> {
+        let mut sizes = [0; TOPICS];
+        let mut unknown = None;
+        let mut known_len = 0;
+        for (i, uid) in self.topics().iter().enumerate() {
+            match lookup(uid).and_then(|info| info.size) {
+                Some(size) => {
+                    sizes[i] = size;
+                    known_len += size;
+                }
+                None if unknown.is_none() => unknown = Some(i),
+                None => return Err(SubscriptionError::UnknownSizes),
+            }
+        }
+
+        match unknown {
+            Some(i) if data_len >= known_len => sizes[i] = data_len - known_len,
+            None if data_len == known_len => {}
+            _ => return Err(SubscriptionError::LengthMismatch(known_len, data_len)),
+        }
+
+        Ok(sizes)
+    }
+}
+
+/// Iterator over the (topic, data) pairs of a push
+#[derive(Debug, Clone)]
+pub struct TopicSplit<'a, const TOPICS: usize> {
+    topics: &'a [VBusUid],
+    sizes: [usize; TOPICS],
+    index: usize,
+    data: &'a [u8],
+}
+
+impl<'a, const TOPICS: usize> TopicSplit<'a, TOPICS> {
+    /// Decode every topic with the decoder of the catalog, `None` for unknown layouts
+    pub fn samples(self) -> impl Iterator<Item = (VBusUid, Option<TopicSample<'a>>)> {
+        self.map(|(uid, data)| {
+            let sample = lookup(&uid).and_then(|info| info.decode?(data));
+            (uid, sample)
+        })
+    }
+}
+
+impl<'a, const TOPICS: usize> Iterator for TopicSplit<'a, TOPICS> {
+    type Item = (VBusUid, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let uid = *self.topics.get(self.index)?;
+        let (head, tail) = self.data.split_at(self.sizes[self.index]);
+        self.index += 1;
+        self.data = tail;
+        Some((uid, head))
+    }
+}
+
+/// Table of the active streams
+///
+/// Holds up to `STREAMS` streams of up to `TOPICS` topics each.
+pub struct SubscriptionTable<const STREAMS: usize, const TOPICS: usize> {
+    streams: [Option<Subscription<TOPICS>>; STREAMS],
+}
+
+impl<const STREAMS: usize, const TOPICS: usize> Default for SubscriptionTable<STREAMS, TOPICS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const STREAMS: usize, const TOPICS: usize> SubscriptionTable<STREAMS, TOPICS> {
+    pub const fn new() -> Self {
+        SubscriptionTable {
+            streams: [None; STREAMS],
+        }
+    }
+
+    /// Record the stream requested by an ADD_SUB, pending until [`Self::handle_ack`]
+    ///
+    /// A pending request for the same publisher, node and stream ID is replaced. A confirmed
+    /// stream is kept in its own slot until the publisher accepts the new request.
+    pub fn insert<T: AsRef<[u8]>>(
+        &mut self,
+        add_sub: &RMAddSubView<T>,
+    ) -> Result<(), SubscriptionError> {
+        let uids = add_sub
+            .topics()
+            .filter(|uids| uids.len() == add_sub.num_topics() as usize)
+            .ok_or(SubscriptionError::InvalidTopics)?;
+        if uids.len() > TOPICS {
+            return Err(SubscriptionError::TooManyTopics(uids.len()));
+        }
+
+        let mut topics = [VBusUid { uid: [0; 8] }; TOPICS];
+        topics[..uids.len()].copy_from_slice(uids);
+        let subscription = Subscription {
+            publisher_id: add_sub.packet.receiver_id(),
+            node_id: add_sub.sub_node_id(),
+            stream_id: add_sub.sub_stream_id(),
+            frequency: add_sub.frequency(),
+            sequence_number: add_sub.packet.sequence_number(),
+            confirmed: false,
+            topics,
+            num_topics: uids.len(),
+        };
+
+        let slot = match self.position(
+            subscription.publisher_id,
+            subscription.node_id,
+            subscription.stream_id,
+            false,
+        ) {
+            Some(slot) => slot,
+            None => self
+                .streams
+                .iter()
+                .position(|s| s.is_none())
+                .ok_or(SubscriptionError::TableFull)?,
+        };
+        self.streams[slot] = Some(subscription);
+
+        Ok(())
+    }
+
+    /// Confirm or drop the pending stream answered by an ADD_SUB ACK
+    ///
+    /// The ACK is matched on its sequence number, its sender (the publisher) and its receiver
+    /// (the node). Returns the stream, which is confirmed if the ACK is positive and removed
+    /// from the table otherwise. A positive ACK replaces the confirmed stream with the same
+    /// publisher, node and stream ID, a negative one leaves it untouched.
+    pub fn handle_ack<T: AsRef<[u8]>>(
+        &mut self,
+        ack: &RMAddSubAckView<T>,
+    ) -> Option<Subscription<TOPICS>> {
+        let (publisher_id, node_id) = (ack.packet.sender_id(), ack.packet.receiver_id());
+        let sequence_number = ack.packet.sequence_number();
+        let slot = self.streams.iter().position(|s| {
+            matches!(s, Some(s) if !s.confirmed
+                && s.publisher_id == publisher_id
+                && s.node_id == node_id
+                && s.sequence_number == sequence_number)
+        })?;
+
+        if ack.is_ok() {
+            let subscription = self.streams[slot].as_mut()?;
+            subscription.confirmed = true;
+            let subscription = *subscription;
+            if let Some(old) = self.streams.iter().enumerate().position(|(i, s)| {
+                i != slot
+                    && matches!(s, Some(s) if s.confirmed
+                        && s.publisher_id == subscription.publisher_id
+                        && s.node_id == subscription.node_id
+                        && s.stream_id == subscription.stream_id)
+            }) {
+                self.streams[old] = None;
+            }
+            Some(subscription)
+        } else {
+            self.streams[slot].take()
+        }
+    }
+
+    /// Forget the stream removed by a DEL_SUB request, sent to the publisher
+    ///
+    /// A pending request for the stream is dropped too. Returns the confirmed stream, or the
+    /// pending one if there was none.
+    pub fn remove<T: AsRef<[u8]>>(
+        &mut self,
+        del_sub: &RMDelSubView<T>,
+    ) -> Option<Subscription<TOPICS>> {
+        let key = (
+            del_sub.packet.receiver_id(),
+            del_sub.sub_node_id(),
+            del_sub.sub_stream_id(),
+        );
+        let pending = self
+            .position(key.0, key.1, key.2, false)
+            .and_then(|slot| self.streams[slot].take());
+        let confirmed = self
+            .position(key.0, key.1, key.2, true)
+            .and_then(|slot| self.streams[slot].take());
+        confirmed.or(pending)
+    }
+
+    /// Forget all the streams of a node, e.g. after it was reset
+    pub fn remove_node(&mut self, node_id: u8) {
+        for stream in self.streams.iter_mut() {
+            if matches!(stream, Some(s) if s.node_id == node_id) {
+                *stream = None;
+            }
+        }
+    }
+
+    /// Stream of a publisher and node, the confirmed one if any, otherwise the pending request
+    pub fn get(
+        &self,
+        publisher_id: u8,
+        node_id: u8,
+        stream_id: u8,
+    ) -> Option<&Subscription<TOPICS>> {
+        let slot = self
+            .position(publisher_id, node_id, stream_id, true)
+            .or_else(|| self.position(publisher_id, node_id, stream_id, false))?;
+        self.streams[slot].as_ref()
+    }
+
+    /// Split the data of a push into its topics
+    pub fn split<'a, T: AsRef<[u8]>>(
+        &'a self,
+        push: &'a RMTopicView<T>,
+    ) -> Result<TopicSplit<'a, TOPICS>, SubscriptionError> {
+        self.split_data(
+            push.packet.sender_id(),
+            push.packet.receiver_id(),
+            push.sub_id(),
+            push.data(),
+        )
+    }
+
+    /// Split the push `data` of a confirmed stream into its topics
+    pub fn split_data<'a>(
+        &'a self,
+        publisher_id: u8,
+        node_id: u8,
+        stream_id: u8,
+        data: &'a [u8],
+    ) -> Result<TopicSplit<'a, TOPICS>, SubscriptionError> {
+        let subscription = self
+            .get(publisher_id, node_id, stream_id)
+            .filter(|s| s.confirmed)
+            .ok_or(SubscriptionError::UnknownStream(
+                publisher_id,
+                node_id,
+                stream_id,
+            ))?;
+
+        Ok(TopicSplit {
+            topics: subscription.topics(),
+            sizes: subscription.sizes(data.len())?,
+            index: 0,
+            data,
+        })
+    }
+
+    /// Number of streams, pending requests included
+    pub fn len(&self) -> usize {
+        self.streams.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget all the streams
+    pub fn clear(&mut self) {
+        self.streams = [None; STREAMS];
+    }
+
+    fn position(
+        &self,
+        publisher_id: u8,
+        node_id: u8,
+        stream_id: u8,
+        confirmed: bool,
+    ) -> Option<usize> {
+        self.streams.iter().position(|s| {
+            matches!(s, Some(s) if s.confirmed == confirmed
+                && s.publisher_id == publisher_id
+                && s.node_id == node_id
+                && s.stream_id == stream_id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        duss::vbus::{
+            topic_data::{BatteryInfo, EscInfo, TopicData},
+            topic_view::RMAddSubBuilder,
+            topics::{VBUS_BATTERY_STATS, VBUS_ESC_STATE, VBUS_UNK_1},
+        },
+        wire::{RMWireFrameBuf, RMWireFrameBuilder, RMWireFrameView},
+    };
+
+    fn request(table: &mut SubscriptionTable<2, 4>, stream_id: u8, topics: &[VBusUid]) {
+        let header = RMWireFrameBuilder::new()
+            .sender_id(0x04)
+            .receiver_id(0x03)
+            .sequence_number(0x100 + stream_id as u16)
+            .need_ack(true);
+        let mut buf = [0u8; 64];
+        let view = RMAddSubBuilder::new(0x04, stream_id)
+            .topics(topics)
+            .build_into(&header, &mut buf)
+            .unwrap();
+        table.insert(&view).unwrap();
+    }
+
+    fn ack(
+        table: &mut SubscriptionTable<2, 4>,
+        sender_id: u8,
+        sequence_number: u16,
+        retcode: u8,
+    ) -> Option<Subscription<4>> {
+        let header = RMWireFrameBuilder::new()
+            .sender_id(sender_id)
+            .receiver_id(0x04)
+            .sequence_number(sequence_number);
+        let mut buf = [0u8; 32];
+        let mut view = RMAddSubAckView::build_into(&header, &mut buf, 0x03, 0, 0).unwrap();
+        view.packet.payload_mut()[0] = retcode;
+        let crc16 = view.packet.crc16_computed();
+        view.packet.set_packet_crc16_field(crc16);
+        table.handle_ack(&view)
+    }
+
+    fn add_sub(table: &mut SubscriptionTable<2, 4>, stream_id: u8, topics: &[VBusUid]) {
+        request(table, stream_id, topics);
+        assert!(ack(table, 0x03, 0x100 + stream_id as u16, 0).is_some());
+    }
+
+    fn push(stream_id: u8, data: &[u8]) -> RMWireFrameView<RMWireFrameBuf<128>> {
+        let mut payload = [0u8; 100];
+        payload[1] = stream_id;
+        payload[2..2 + data.len()].copy_from_slice(data);
+        RMWireFrameBuilder::new()
+            .sender_id(0x03)
+            .receiver_id(0x04)
+            .cmd_set(0x48)
+            .cmd_id(0x08)
+            .payload(&payload[..2 + data.len()])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_split_capture() {
+        // Push in `topic_view::test::test_topic_view`, stream 0 of the gimbal
+        let buf = [
+            0x55, 0x31, 0x04, 0x53, 0x03, 0x04, 0x8d, 0x04, //
+            0x20, 0x48, 0x08, 0x00, 0x00, 0x99, 0x1d, 0x00, //
+            0x00, 0xf0, 0xa0, 0x73, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0xac, 0x2e, 0xdd, //
+            0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00, 0x3f, //
+            0x41,
+        ];
+        let push = RMTopicView::new(RMWireFrameView::new(&buf[..]));
+
+        let mut table = SubscriptionTable::<2, 4>::new();
+        assert_eq!(
+            table.split(&push).err(),
+            Some(SubscriptionError::UnknownStream(0x03, 0x04, 0))
+        );
+
+        add_sub(
+            &mut table,
+            0,
+            &[
+                VBusUid { uid: VBUS_UNK_1 },
+                VBusUid {
+                    uid: VBUS_BATTERY_STATS,
+                },
+            ],
+        );
+        let mut split = table.split(&push).unwrap();
+
+        let (uid, data) = split.next().unwrap();
+        assert_eq!(uid.uid, VBUS_UNK_1);
+        assert_eq!(data.len(), 24);
+
+        let (uid, data) = split.next().unwrap();
+        assert_eq!(uid.uid, VBUS_BATTERY_STATS);
+        let battery = BatteryInfo::from_bytes(data).unwrap();
+        assert_eq!(battery.voltage_mv(), 11948);
+        assert_eq!(battery.percent(), 84);
+
+        assert!(split.next().is_none());
+    }
+
+    #[test]
+    fn test_split_known_sizes() {
+        let mut table = SubscriptionTable::<2, 4>::new();
+        let topics = [
+            VBusUid {
+                uid: VBUS_BATTERY_STATS,
+            },
+            VBusUid {
+                uid: VBUS_ESC_STATE,
+            },
+        ];
+        add_sub(&mut table, 1, &topics);
+        assert_eq!(table.get(0x03, 0x04, 1).unwrap().topics(), &topics);
+
+        let mut data = [0u8; 46];
+        data[0] = 0x34;
+        data[10] = 0x64;
+        let frame = push(1, &data);
+        let view = RMTopicView::new(RMWireFrameView::new(frame.as_bytes()));
+
+        let mut split = table.split(&view).unwrap();
+        let (_, battery) = split.next().unwrap();
+        assert_eq!(BatteryInfo::from_bytes(battery).unwrap().voltage_mv(), 0x34);
+        let (_, esc) = split.next().unwrap();
+        assert_eq!(EscInfo::from_bytes(esc).unwrap().speed()[0], 0x64);
+        assert!(split.next().is_none());
+
+        let frame = push(1, &data[..45]);
+        let view = RMTopicView::new(RMWireFrameView::new(frame.as_bytes()));
+        assert_eq!(
+            table.split(&view).err(),
+            Some(SubscriptionError::LengthMismatch(46, 45))
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = SubscriptionTable::<2, 4>::new();
+        let topics = [VBusUid { uid: VBUS_UNK_1 }; 2];
+
+        add_sub(&mut table, 0, &topics[..1]);
+        add_sub(&mut table, 0, &topics);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.get(0x03, 0x04, 0).unwrap().topics().len(), 2);
+
+        // Two topics of unknown size
+        let frame = push(0, &[0; 8]);
+        let view = RMTopicView::new(RMWireFrameView::new(frame.as_bytes()));
+        assert_eq!(
+            table.split(&view).err(),
+            Some(SubscriptionError::UnknownSizes)
+        );
+
+        add_sub(&mut table, 1, &topics[..1]);
+        let header = RMWireFrameBuilder::new().sender_id(0x04).receiver_id(0x03);
+        let mut buf = [0u8; 64];
+        let view = RMAddSubBuilder::new(0x04, 2)
+            .topics(&topics[..1])
+            .build_into(&header, &mut buf)
+            .unwrap();
+        assert_eq!(table.insert(&view), Err(SubscriptionError::TableFull));
+
+        let mut buf = [0u8; 64];
+        let view = RMAddSubBuilder::new(0x04, 0)
+            .topics(&[VBusUid { uid: VBUS_UNK_1 }; 5])
+            .build_into(&header, &mut buf)
+            .unwrap();
+        assert_eq!(
+            table.insert(&view),
+            Err(SubscriptionError::TooManyTopics(5))
+        );
+
+        let mut buf = [0u8; 32];
+        let del = RMDelSubView::build_into(&header, &mut buf, 0, 0x04, 1).unwrap();
+        assert!(table.remove(&del).is_some());
+        assert!(table.get(0x03, 0x04, 1).is_none());
+
+        table.remove_node(0x04);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_pending() {
+        let mut table = SubscriptionTable::<2, 4>::new();
+        let topics = [VBusUid {
+            uid: VBUS_BATTERY_STATS,
+        }];
+        let frame = push(0, &[0; 10]);
+        let view = RMTopicView::new(RMWireFrameView::new(frame.as_bytes()));
+
+        // Pushes are not split until the publisher accepts the stream
+        request(&mut table, 0, &topics);
+        assert!(!table.get(0x03, 0x04, 0).unwrap().confirmed);
+        assert_eq!(
+            table.split(&view).err(),
+            Some(SubscriptionError::UnknownStream(0x03, 0x04, 0))
+        );
+
+        // ACKs of another request or from another node
+        assert!(ack(&mut table, 0x03, 0x101, 0).is_none());
+        assert!(ack(&mut table, 0x09, 0x100, 0).is_none());
+
+        let subscription = ack(&mut table, 0x03, 0x100, 0).unwrap();
+        assert!(subscription.confirmed);
+        assert_eq!(subscription.publisher_id, 0x03);
+        assert!(table.split(&view).is_ok());
+        assert!(ack(&mut table, 0x03, 0x100, 0).is_none());
+
+        // The same stream from another publisher
+        let frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0x04)
+            .cmd_set(0x48)
+            .cmd_id(0x08)
+            .payload(&[0; 12])
+            .build::<32>()
+            .unwrap();
+        let other = RMTopicView::new(RMWireFrameView::new(frame.as_bytes()));
+        assert_eq!(
+            table.split(&other).err(),
+            Some(SubscriptionError::UnknownStream(0x09, 0x04, 0))
+        );
+
+        // A rejected repeat of the request keeps the confirmed stream
+        request(&mut table, 0, &topics);
+        assert_eq!(table.len(), 2);
+        assert!(table.get(0x03, 0x04, 0).unwrap().confirmed);
+        assert!(table.split(&view).is_ok());
+        assert!(!ack(&mut table, 0x03, 0x100, 0x01).unwrap().confirmed);
+        assert_eq!(table.len(), 1);
+        assert!(table.split(&view).is_ok());
+
+        // An accepted one replaces it
+        let both = [
+            topics[0],
+            VBusUid {
+                uid: VBUS_ESC_STATE,
+            },
+        ];
+        request(&mut table, 0, &both);
+        assert!(ack(&mut table, 0x03, 0x100, 0).unwrap().confirmed);
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.get(0x03, 0x04, 0).unwrap().topics(), &both);
+        assert_eq!(
+            table.split(&view).err(),
+            Some(SubscriptionError::LengthMismatch(46, 10))
+        );
+
+        // A rejected new request is dropped
+        table.clear();
+        request(&mut table, 0, &topics);
+        assert!(!ack(&mut table, 0x03, 0x100, 0x01).unwrap().confirmed);
+        assert!(table.is_empty());
+    }
+}
@@ -0,0 +1,570 @@
+//! Decoders for the VBUS topics of the FC and of the SDK
+//!
+//! Each topic is a fixed-size record in the data of a push, in the order of the
+//! subscription. The structs hold little-endian byte arrays, so they can be cast from
+//! the push data at any alignment without copying.
+//!
+//! The layouts are the ones of the matching RoboMaster SDK topics. The battery layout of the
+//! FC topic is confirmed by a capture.
+//! The ESC, IMU, attitude, position and velocity layouts of the FC topics are unverified:
+//! no push carrying them has been captured yet.
+//!
+//! [`super::topics::VBUS_TURRET_YAW`] has no decoder. RoboStack subscribes to it, but it
+//! has no SDK counterpart and its data has never been captured, so its layout is unknown.
+//!
+//! # Example
+//!
+//! ```
+//! use robomaster_s1_proto::duss::vbus::topic_data::{BatteryInfo, TopicData};
+//!
+//! let data = [0xac, 0x2e, 0xdd, 0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00];
+//! let battery = BatteryInfo::from_bytes(&data).unwrap();
+//!
+//! assert_eq!(battery.voltage_mv(), 11948);
+//! assert_eq!(battery.percent(), 84);
+//! ```
+
+use bytemuck::{Pod, Zeroable};
+
+use super::topics::{
+    SDK_GIMBAL_POS, SDK_NS_SA_STATUS, VBUS_BASE_POSITION, VBUS_BATTERY_STATS,
+    VBUS_CHASSIS_VELOCITY, VBUS_ESC_STATE, VBUS_IMU_DATA, VBUS_TURRET_ATTITUDE_RPY,
+};
+
+/// A topic with a known layout
+///
+/// The UIDs using a layout are listed in the catalog of [`super::topics`].
+pub trait TopicData: Pod {
+    /// UID used to subscribe to the topic
+    ///
+    /// The FC topic when the SDK has one with the same layout.
+    const UID: [u8; 8];
+
+    /// Length of the topic in the push data
+    const LEN: usize = core::mem::size_of::<Self>();
+
+    /// Cast `data`, which must be exactly [`Self::LEN`] bytes
+    fn from_bytes(data: &[u8]) -> Option<&Self> {
+        bytemuck::try_from_bytes(data).ok()
+    }
+
+    /// Cast the start of `data`, returning the remaining bytes
+    fn from_prefix(data: &[u8]) -> Option<(&Self, &[u8])> {
+        if data.len() < Self::LEN {
+            return None;
+        }
+        let (head, tail) = data.split_at(Self::LEN);
+        Some((bytemuck::from_bytes(head), tail))
+    }
+}
+
+/// Decoded data of a topic
+#[derive(Debug, Clone, Copy)]
+pub enum TopicSample<'a> {
+    Battery(&'a BatteryInfo),
+    Esc(&'a EscInfo),
+    Imu(&'a ImuData),
+    Attitude(&'a Attitude),
+    Position(&'a Position),
+    Velocity(&'a Velocity),
+    ChassisStatus(&'a ChassisStatus),
+    GimbalAngles(&'a GimbalAngles),
+}
+
+fn f32_at(bytes: &[u8], index: usize) -> f32 {
+    let start = index * 4;
+    f32::from_le_bytes([
+        bytes[start],
+        bytes[start + 1],
+        bytes[start + 2],
+        bytes[start + 3],
+    ])
+}
+
+/// Battery state
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct BatteryInfo {
+    voltage: [u8; 2],
+    temperature: [u8; 2],
+    current: [u8; 4],
+    percent: u8,
+    reserved: u8,
+}
+
+impl BatteryInfo {
+    /// Voltage (mV)
+    pub fn voltage_mv(&self) -> u16 {
+        u16::from_le_bytes(self.voltage)
+    }
+
+    /// Temperature (0.1 °C)
+    pub fn temperature_decicelsius(&self) -> i16 {
+        i16::from_le_bytes(self.temperature)
+    }
+
+    /// Current (mA), negative when discharging
+    pub fn current_ma(&self) -> i32 {
+        i32::from_le_bytes(self.current)
+    }
+
+    /// State of charge (%)
+    pub fn percent(&self) -> u8 {
+        self.percent
+    }
+}
+
+impl core::fmt::Debug for BatteryInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BatteryInfo")
+            .field("voltage_mv", &self.voltage_mv())
+            .field("temperature_decicelsius", &self.temperature_decicelsius())
+            .field("current_ma", &self.current_ma())
+            .field("percent", &self.percent())
+            .finish()
+    }
+}
+
+impl TopicData for BatteryInfo {
+    const UID: [u8; 8] = VBUS_BATTERY_STATS;
+}
+
+/// State of the four wheel ESCs
+///
+/// Unverified on the FC topic, the layout is taken from the SDK.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct EscInfo {
+    speed: [[u8; 2]; 4],
+    angle: [[u8; 2]; 4],
+    timestamp: [[u8; 4]; 4],
+    state: [u8; 4],
+}
+
+impl EscInfo {
+    /// Wheel speed (rpm)
+    pub fn speed(&self) -> [i16; 4] {
+        self.speed.map(i16::from_le_bytes)
+    }
+
+    /// Wheel angle (encoder ticks, 0..32767 per turn)
+    pub fn angle(&self) -> [u16; 4] {
+        self.angle.map(u16::from_le_bytes)
+    }
+
+    /// Timestamp of each measurement
+    pub fn timestamp(&self) -> [u32; 4] {
+        self.timestamp.map(u32::from_le_bytes)
+    }
+
+    /// ESC state, 0 when the ESC works normally
+    pub fn state(&self) -> [u8; 4] {
+        self.state
+    }
+}
+
+impl core::fmt::Debug for EscInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EscInfo")
+            .field("speed", &self.speed())
+            .field("angle", &self.angle())
+            .field("timestamp", &self.timestamp())
+            .field("state", &self.state())
+            .finish()
+    }
+}
+
+impl TopicData for EscInfo {
+    const UID: [u8; 8] = VBUS_ESC_STATE;
+}
+
+/// IMU measurements in the body frame
+///
+/// Unverified on the FC topic, the layout is taken from the SDK.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ImuData {
+    data: [u8; 24],
+}
+
+impl ImuData {
+    /// Acceleration along x, y, z (g)
+    pub fn accel(&self) -> [f32; 3] {
+        [0, 1, 2].map(|i| f32_at(&self.data, i))
+    }
+
+    /// Angular velocity around x, y, z
+    pub fn gyro(&self) -> [f32; 3] {
+        [3, 4, 5].map(|i| f32_at(&self.data, i))
+    }
+}
+
+impl core::fmt::Debug for ImuData {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ImuData")
+            .field("accel", &self.accel())
+            .field("gyro", &self.gyro())
+            .finish()
+    }
+}
+
+impl TopicData for ImuData {
+    const UID: [u8; 8] = VBUS_IMU_DATA;
+}
+
+/// Yaw, pitch and roll
+///
+/// The layout of [`super::topics::VBUS_TURRET_ATTITUDE_RPY`]. The body it describes is
+/// given by the topic: the turret for that one, the chassis for the SDK `attitude_info`.
+/// Unverified on the FC topic, the layout is taken from the SDK.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Attitude {
+    data: [u8; 12],
+}
+
+impl Attitude {
+    /// Yaw (°)
+    pub fn yaw(&self) -> f32 {
+        f32_at(&self.data, 0)
+    }
+
+    /// Pitch (°)
+    pub fn pitch(&self) -> f32 {
+        f32_at(&self.data, 1)
+    }
+
+    /// Roll (°)
+    pub fn roll(&self) -> f32 {
+        f32_at(&self.data, 2)
+    }
+}
+
+impl core::fmt::Debug for Attitude {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Attitude")
+            .field("yaw", &self.yaw())
+            .field("pitch", &self.pitch())
+            .field("roll", &self.roll())
+            .finish()
+    }
+}
+
+impl TopicData for Attitude {
+    const UID: [u8; 8] = VBUS_TURRET_ATTITUDE_RPY;
+}
+
+/// Position of the chassis relative to where it was powered on
+///
+/// Unverified on the FC topic, the layout is taken from the SDK.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Position {
+    data: [u8; 12],
+}
+
+impl Position {
+    /// x (m)
+    pub fn x(&self) -> f32 {
+        f32_at(&self.data, 0)
+    }
+
+    /// y (m)
+    pub fn y(&self) -> f32 {
+        f32_at(&self.data, 1)
+    }
+
+    /// z
+    pub fn z(&self) -> f32 {
+        f32_at(&self.data, 2)
+    }
+}
+
+impl core::fmt::Debug for Position {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Position")
+            .field("x", &self.x())
+            .field("y", &self.y())
+            .field("z", &self.z())
+            .finish()
+    }
+}
+
+impl TopicData for Position {
+    const UID: [u8; 8] = VBUS_BASE_POSITION;
+}
+
+/// Velocity of the chassis
+///
+/// Unverified on the FC topic, the layout is taken from the SDK.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Velocity {
+    data: [u8; 24],
+}
+
+impl Velocity {
+    /// Velocity along x, y, z in the frame fixed at power on (m/s)
+    pub fn world(&self) -> [f32; 3] {
+        [0, 1, 2].map(|i| f32_at(&self.data, i))
+    }
+
+    /// Velocity along x, y, z in the body frame (m/s)
+    pub fn body(&self) -> [f32; 3] {
+        [3, 4, 5].map(|i| f32_at(&self.data, i))
+    }
+}
+
+impl core::fmt::Debug for Velocity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Velocity")
+            .field("world", &self.world())
+            .field("body", &self.body())
+            .finish()
+    }
+}
+
+impl TopicData for Velocity {
+    const UID: [u8; 8] = VBUS_CHASSIS_VELOCITY;
+}
+
+/// Chassis status flags, SDK topic `ns_sa_status`
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct ChassisStatus {
+    flags: [u8; 2],
+}
+
+impl ChassisStatus {
+    fn flag(&self, bit: usize) -> bool {
+        self.flags[bit / 8] & (1 << (bit % 8)) != 0
+    }
+
+    /// The chassis is not moving
+    pub fn is_static(&self) -> bool {
+        self.flag(0)
+    }
+
+    pub fn up_hill(&self) -> bool {
+        self.flag(1)
+    }
+
+    pub fn down_hill(&self) -> bool {
+        self.flag(2)
+    }
+
+    pub fn on_slope(&self) -> bool {
+        self.flag(3)
+    }
+
+    /// The chassis was picked up
+    pub fn is_pick_up(&self) -> bool {
+        self.flag(4)
+    }
+
+    pub fn slip(&self) -> bool {
+        self.flag(5)
+    }
+
+    /// Impact along x, y, z
+    pub fn impact(&self) -> [bool; 3] {
+        [self.flag(6), self.flag(7), self.flag(8)]
+    }
+
+    pub fn roll_over(&self) -> bool {
+        self.flag(9)
+    }
+
+    /// Standing still on a slope
+    pub fn hill_static(&self) -> bool {
+        self.flag(10)
+    }
+}
+
+impl core::fmt::Debug for ChassisStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ChassisStatus")
+            .field("is_static", &self.is_static())
+            .field("up_hill", &self.up_hill())
+            .field("down_hill", &self.down_hill())
+            .field("on_slope", &self.on_slope())
+            .field("is_pick_up", &self.is_pick_up())
+            .field("slip", &self.slip())
+            .field("impact", &self.impact())
+            .field("roll_over", &self.roll_over())
+            .field("hill_static", &self.hill_static())
+            .finish()
+    }
+}
+
+impl TopicData for ChassisStatus {
+    const UID: [u8; 8] = SDK_NS_SA_STATUS;
+}
+
+/// Gimbal angles, SDK topic `gimbal_pos`
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct GimbalAngles {
+    data: [[u8; 2]; 4],
+}
+
+impl GimbalAngles {
+    /// Yaw relative to the ground (°)
+    pub fn yaw_ground(&self) -> i16 {
+        i16::from_le_bytes(self.data[0])
+    }
+
+    /// Pitch relative to the ground (°)
+    pub fn pitch_ground(&self) -> i16 {
+        i16::from_le_bytes(self.data[1])
+    }
+
+    /// Yaw relative to the chassis (°)
+    pub fn yaw(&self) -> i16 {
+        i16::from_le_bytes(self.data[2])
+    }
+
+    /// Pitch relative to the chassis (°)
+    pub fn pitch(&self) -> i16 {
+        i16::from_le_bytes(self.data[3])
+    }
+}
+
+impl core::fmt::Debug for GimbalAngles {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GimbalAngles")
+            .field("yaw_ground", &self.yaw_ground())
+            .field("pitch_ground", &self.pitch_ground())
+            .field("yaw", &self.yaw())
+            .field("pitch", &self.pitch())
+            .finish()
+    }
+}
+
+impl TopicData for GimbalAngles {
+    const UID: [u8; 8] = SDK_GIMBAL_POS;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn f32_bytes<const N: usize>(values: [f32; N], out: &mut [u8]) {
+        for (chunk, value) in out.chunks_exact_mut(4).zip(values) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn test_lengths() {
+        assert_eq!(BatteryInfo::LEN, 10);
+        assert_eq!(EscInfo::LEN, 36);
+        assert_eq!(ImuData::LEN, 24);
+        assert_eq!(Attitude::LEN, 12);
+        assert_eq!(Position::LEN, 12);
+        assert_eq!(Velocity::LEN, 24);
+        assert_eq!(ChassisStatus::LEN, 2);
+        assert_eq!(GimbalAngles::LEN, 8);
+    }
+
+    #[test]
+    fn test_uids() {
+        use crate::duss::vbus::topics::{lookup, VBusUid};
+
+        fn check<T: TopicData>(name: &str) {
+            let info = lookup(&VBusUid { uid: T::UID }).unwrap();
+            assert_eq!(info.name, name);
+            assert_eq!(info.size, Some(T::LEN));
+        }
+
+        check::<BatteryInfo>("battery");
+        check::<EscInfo>("esc_state");
+        check::<ImuData>("imu");
+        check::<Attitude>("attitude");
+        check::<Position>("position");
+        check::<Velocity>("velocity");
+        check::<ChassisStatus>("ns_sa_status");
+        check::<GimbalAngles>("gimbal_pos");
+    }
+
+    #[test]
+    fn test_sdk_topics() {
+        let status = ChassisStatus::from_bytes(&[0b0001_0001, 0b0000_0011]).unwrap();
+        assert!(status.is_static());
+        assert!(status.is_pick_up());
+        assert!(!status.slip());
+        assert_eq!(status.impact(), [false, false, true]);
+        assert!(status.roll_over());
+        assert!(!status.hill_static());
+
+        let angles =
+            GimbalAngles::from_bytes(&[0x2c, 0x01, 0xf6, 0xff, 0x00, 0x00, 0x05, 0x00]).unwrap();
+        assert_eq!(angles.yaw_ground(), 300);
+        assert_eq!(angles.pitch_ground(), -10);
+        assert_eq!(angles.yaw(), 0);
+        assert_eq!(angles.pitch(), 5);
+    }
+
+    #[test]
+    fn test_battery_capture() {
+        // Data of the push in `topic_view::test::test_topic_view`
+        let data = [
+            0x99, 0x1d, 0x00, 0x00, 0xf0, 0xa0, 0x73, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0xac, 0x2e, 0xdd, 0x00, 0x17, 0xfd, 0xff, 0xff, //
+            0x54, 0x00,
+        ];
+
+        let battery = BatteryInfo::from_bytes(&data[24..]).unwrap();
+        assert_eq!(battery.voltage_mv(), 11948);
+        assert_eq!(battery.temperature_decicelsius(), 221);
+        assert_eq!(battery.current_ma(), -745);
+        assert_eq!(battery.percent(), 84);
+
+        // Unaligned and wrongly sized input
+        assert!(BatteryInfo::from_bytes(&data[23..]).is_none());
+        assert!(BatteryInfo::from_prefix(&data[25..]).is_none());
+        let (_, rest) = BatteryInfo::from_prefix(&data[23..]).unwrap();
+        assert_eq!(rest, &[0x00]);
+    }
+
+    #[test]
+    fn test_esc() {
+        let mut data = [0u8; 36];
+        data[0..2].copy_from_slice(&(-120i16).to_le_bytes());
+        data[14..16].copy_from_slice(&32000u16.to_le_bytes());
+        data[16..20].copy_from_slice(&0x0102_0304u32.to_le_bytes());
+        data[35] = 1;
+
+        let esc = EscInfo::from_bytes(&data).unwrap();
+        assert_eq!(esc.speed(), [-120, 0, 0, 0]);
+        assert_eq!(esc.angle(), [0, 0, 0, 32000]);
+        assert_eq!(esc.timestamp(), [0x0102_0304, 0, 0, 0]);
+        assert_eq!(esc.state(), [0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_float_topics() {
+        let mut data = [0u8; 25];
+        f32_bytes([0.0, 0.5, -1.0, 10.0, 20.0, 30.0], &mut data[1..]);
+
+        let imu = ImuData::from_bytes(&data[1..]).unwrap();
+        assert_eq!(imu.accel(), [0.0, 0.5, -1.0]);
+        assert_eq!(imu.gyro(), [10.0, 20.0, 30.0]);
+
+        let velocity = Velocity::from_bytes(&data[1..]).unwrap();
+        assert_eq!(velocity.world(), [0.0, 0.5, -1.0]);
+        assert_eq!(velocity.body(), [10.0, 20.0, 30.0]);
+
+        let (attitude, rest) = Attitude::from_prefix(&data[1..]).unwrap();
+        assert_eq!(attitude.yaw(), 0.0);
+        assert_eq!(attitude.pitch(), 0.5);
+        assert_eq!(attitude.roll(), -1.0);
+
+        let position = Position::from_bytes(rest).unwrap();
+        assert_eq!(position.x(), 10.0);
+        assert_eq!(position.y(), 20.0);
+        assert_eq!(position.z(), 30.0);
+    }
+}
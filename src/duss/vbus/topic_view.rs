@@ -1,9 +1,10 @@
-use crate::wire::RMWireFrameView;
+use crate::wire::{BuildError, FrameError, RMWireFrameBuilder, RMWireFrameView};
 use bytemuck::{self};
 
-use super::topics::VBusUid;
+use super::{topics::VBusUid, CMDID_VBUS_ADD_SUB, CMDID_VBUS_PUSH_MSG, CMDSET_VBUS};
 
 /// Published Topic Packet
+#[derive(Debug)]
 pub struct RMTopicView<T: AsRef<[u8]>> {
     pub packet: RMWireFrameView<T>,
 }
@@ -13,6 +14,23 @@ impl<T: AsRef<[u8]>> RMTopicView<T> {
         RMTopicView { packet }
     }
 
+    /// Create a view after checking the frame, the command and the payload length
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMTopicView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMTopicView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(CMDSET_VBUS, CMDID_VBUS_PUSH_MSG)?;
+        if packet.payload().len() < 2 {
+            return Err(FrameError::InvalidPayloadLength(packet.payload().len()));
+        }
+
+        Ok(())
+    }
+
     pub fn sub_mode(&self) -> u8 {
         self.packet.payload()[0]
     }
@@ -47,6 +65,7 @@ impl<T: AsRef<[u8]>> RMTopicView<T> {
 ///     - Number of topics (0x1)
 ///     - Array of topics
 ///     - Frequency of the topic (2 bytes)
+#[derive(Debug)]
 pub struct RMAddSubView<T: AsRef<[u8]>> {
     pub packet: RMWireFrameView<T>,
 }
@@ -56,6 +75,28 @@ impl<T: AsRef<[u8]>> RMAddSubView<T> {
         RMAddSubView { packet }
     }
 
+    /// Create a view after checking the frame, the command and the payload length
+    ///
+    /// The payload length must match the number of topics.
+    pub fn try_new(packet: RMWireFrameView<T>) -> Result<RMAddSubView<T>, FrameError> {
+        packet.check()?;
+        Self::check_layout(&packet)?;
+        Ok(RMAddSubView { packet })
+    }
+
+    /// Check the command and the payload length, the CRCs are not checked
+    pub(crate) fn check_layout(packet: &RMWireFrameView<T>) -> Result<(), FrameError> {
+        packet.check_command(CMDSET_VBUS, CMDID_VBUS_ADD_SUB)?;
+        let payload = packet.payload();
+        if payload.len() < 7
+            || payload.len() != 7 + payload[4] as usize * core::mem::size_of::<VBusUid>()
+        {
+            return Err(FrameError::InvalidPayloadLength(payload.len()));
+        }
+
+        Ok(())
+    }
+
     pub fn sub_node_id(&self) -> u8 {
         self.packet.payload()[0]
     }
@@ -92,13 +133,190 @@ impl<T: AsRef<[u8]>> RMAddSubView<T> {
 }
 
 /// Mutable Methods
-impl<T: AsRef<[u8]> + AsMut<[u8]>> RMAddSubView<T> {}
+impl<T: AsRef<[u8]> + AsMut<[u8]>> RMAddSubView<T> {
+    pub fn set_sub_node_id(&mut self, node_id: u8) {
+        self.packet.payload_mut()[0] = node_id;
+    }
+
+    pub fn set_sub_stream_id(&mut self, stream_id: u8) {
+        self.packet.payload_mut()[1] = stream_id;
+    }
+
+    pub fn set_timestamp_requested(&mut self, timestamp: bool) {
+        let payload = self.packet.payload_mut();
+        if timestamp {
+            payload[2] |= 0b1;
+        } else {
+            payload[2] &= !0b1;
+        }
+    }
+
+    pub fn set_stop_when_disconnected(&mut self, stop: bool) {
+        let payload = self.packet.payload_mut();
+        if stop {
+            payload[2] |= 0b10;
+        } else {
+            payload[2] &= !0b10;
+        }
+    }
+
+    pub fn set_sub_mode(&mut self, sub_mode: u8) {
+        self.packet.payload_mut()[3] = sub_mode;
+    }
+
+    /// The topics can be replaced in place, their number is fixed by the frame length
+    pub fn topics_mut(&mut self) -> Option<&mut [VBusUid]> {
+        let payload = self.packet.payload_mut();
+        let len = payload.len();
+        bytemuck::try_cast_slice_mut(&mut payload[5..len - 2]).ok()
+    }
+
+    pub fn set_frequency(&mut self, frequency: u16) {
+        let payload = self.packet.payload_mut();
+        let len = payload.len();
+        payload[len - 2..].copy_from_slice(&frequency.to_le_bytes());
+    }
+}
+
+/// Subscriber Add Packet Builder
+///
+/// Builds a `CMDID_VBUS_ADD_SUB` frame sized for the topics. The header fields (sender,
+/// sequence number, flags) are taken from a [`RMWireFrameBuilder`], the receiver defaults
+/// to the FC.
+///
+/// # Example
+///
+/// ```
+/// use robomaster_s1_proto::duss::vbus::topic_view::RMAddSubBuilder;
+/// use robomaster_s1_proto::duss::vbus::topics::{VBusUid, VBUS_BATTERY_STATS};
+/// use robomaster_s1_proto::wire::RMWireFrameBuilder;
+///
+/// let topics = [VBusUid { uid: VBUS_BATTERY_STATS }];
+/// let header = RMWireFrameBuilder::new()
+///     .sender_id(0x09)
+///     .sequence_number(2)
+///     .need_ack_after_exec(true);
+///
+/// let mut buf = [0u8; 64];
+/// let frame = RMAddSubBuilder::new(0x09, 0)
+///     .timestamp_requested(true)
+///     .stop_when_disconnected(true)
+///     .topics(&topics)
+///     .frequency(1)
+///     .build_into(&header, &mut buf)
+///     .unwrap();
+///
+/// assert!(frame.packet.is_valid());
+/// assert_eq!(frame.packet.receiver_id(), 0x03);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RMAddSubBuilder<'a> {
+    node_id: u8,
+    stream_id: u8,
+    timestamp_requested: bool,
+    stop_when_disconnected: bool,
+    sub_mode: u8,
+    topics: &'a [VBusUid],
+    frequency: u16,
+}
+
+impl<'a> RMAddSubBuilder<'a> {
+    pub const fn new(node_id: u8, stream_id: u8) -> Self {
+        RMAddSubBuilder {
+            node_id,
+            stream_id,
+            timestamp_requested: false,
+            stop_when_disconnected: false,
+            sub_mode: 0,
+            topics: &[],
+            frequency: 0,
+        }
+    }
+
+    pub const fn timestamp_requested(mut self, timestamp: bool) -> Self {
+        self.timestamp_requested = timestamp;
+        self
+    }
+
+    pub const fn stop_when_disconnected(mut self, stop: bool) -> Self {
+        self.stop_when_disconnected = stop;
+        self
+    }
+
+    pub const fn sub_mode(mut self, sub_mode: u8) -> Self {
+        self.sub_mode = sub_mode;
+        self
+    }
+
+    pub const fn topics(mut self, topics: &'a [VBusUid]) -> Self {
+        self.topics = topics;
+        self
+    }
+
+    /// Push frequency in Hz
+    pub const fn frequency(mut self, frequency: u16) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub const fn node_id(&self) -> u8 {
+        self.node_id
+    }
+
+    pub const fn stream_id(&self) -> u8 {
+        self.stream_id
+    }
+
+    pub const fn payload_len(&self) -> usize {
+        7 + core::mem::size_of_val(self.topics)
+    }
+
+    /// Time between two pushes in milliseconds, `None` for a frequency of 0
+    pub const fn period_ms(&self) -> Option<u32> {
+        match self.frequency {
+            0 => None,
+            frequency => Some(1000_u32.div_ceil(frequency as u32)),
+        }
+    }
+
+    /// Write the frame to the start of `buf`
+    ///
+    /// CMD_SET, CMD_ID and the payload of `header` are replaced.
+    pub fn build_into<'b>(
+        &self,
+        header: &RMWireFrameBuilder<'_>,
+        buf: &'b mut [u8],
+    ) -> Result<RMAddSubView<&'b mut [u8]>, BuildError> {
+        if self.topics.len() > u8::MAX as usize {
+            return Err(BuildError::PayloadTooLarge(self.payload_len()));
+        }
+
+        let packet = header
+            .clone()
+            .cmd_set(CMDSET_VBUS)
+            .cmd_id(CMDID_VBUS_ADD_SUB)
+            .build_into_with(buf, self.payload_len(), |payload| {
+                payload[0] = self.node_id;
+                payload[1] = self.stream_id;
+                payload[2] =
+                    (self.stop_when_disconnected as u8) << 1 | self.timestamp_requested as u8;
+                payload[3] = self.sub_mode;
+                payload[4] = self.topics.len() as u8;
+                let (topics, frequency) =
+                    payload[5..].split_at_mut(core::mem::size_of_val(self.topics));
+                topics.copy_from_slice(bytemuck::cast_slice(self.topics));
+                frequency.copy_from_slice(&self.frequency.to_le_bytes());
+            })?;
+
+        Ok(RMAddSubView::new(packet))
+    }
+}
 
 #[cfg(test)]
 mod test {
     extern crate std;
 
-    use crate::duss::vbus::{CMDID_VBUS_ADD_SUB, CMDID_VBUS_PUSH_MSG, CMDSET_VBUS};
+    use crate::duss::vbus::{CMDID_VBUS_ADD_SUB, CMDSET_VBUS};
 
     use super::*;
 
@@ -127,7 +345,7 @@ mod test {
 
         assert_eq!(topic.data().len(), 34);
         assert_eq!(topic.packet.cmd_set(), crate::duss::vbus::CMDSET_VBUS);
-        assert_eq!(topic.packet.is_valid(), true);
+        assert!(topic.packet.is_valid());
         assert_eq!(topic.sub_mode(), 0x00);
         assert_eq!(topic.sub_id(), 0x00); // Subscribe Session ID
         assert_eq!(
@@ -161,6 +379,26 @@ mod test {
         assert_eq!(84, topic.data()[32]);
     }
 
+    #[test]
+    fn test_topic_view_try_new() {
+        // Capture of `test_topic_view`
+        let buf = [
+            0x55, 0x31, 0x04, 0x53, 0x03, 0x04, 0x8d, 0x04, //
+            0x20, 0x48, 0x08, 0x00, 0x00, 0x99, 0x1d, 0x00, //
+            0x00, 0xf0, 0xa0, 0x73, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0xac, 0x2e, 0xdd, //
+            0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00, 0x3f, //
+            0x41,
+        ];
+
+        assert!(RMTopicView::try_new(RMWireFrameView::new(&buf)).is_ok());
+        assert_eq!(
+            RMAddSubView::try_new(RMWireFrameView::new(&buf)).err(),
+            Some(FrameError::WrongCommand(CMDSET_VBUS, CMDID_VBUS_PUSH_MSG))
+        );
+    }
+
     #[test]
     fn test_subscribe_add_parse() {
         // A sample subscribe add packet with 7 topics with 50Hz update rate
@@ -189,7 +427,7 @@ mod test {
             crate::duss::vbus::CMDID_VBUS_ADD_SUB
         );
 
-        assert_eq!(addsub_view.packet.is_valid(), true);
+        assert!(addsub_view.packet.is_valid());
 
         assert_eq!(addsub_view.sub_node_id(), 0x09);
         assert_eq!(addsub_view.sub_mode(), 0x00);
@@ -232,33 +470,22 @@ mod test {
 
     #[test]
     fn test_robostack_init_commands() {
-        let buf1 = [
-            0x55, 0x12, 0x04, 0xFF, 0x09, 0x03, 0x01, 0x00, 0x40, 0x48, 0x01, 0x09, 0x00, 0x00,
-            0x00, 0x03, 0xFF, 0xFF,
-        ];
-        let buf2 = [
-            0x55, 0x1C, 0x04, 0xFF, 0x09, 0x03, 0x02, 0x00, 0x40, 0x48, 0x03, 0x09, 0x00, 0x03,
-            0x00, 0x01, 0xFB, 0xDC, 0xF5, 0xD7, 0x03, 0x00, 0x02, 0x00, 0x01, 0x00, 0xFF, 0xFF,
-        ];
-        let buf3 = [
-            0x55, 0x12, 0x04, 0xFF, 0x09, 0x03, 0x03, 0x00, 0x40, 0x48, 0x01, 0x09, 0x00, 0x00,
-            0x00, 0x03, 0xFF, 0xFF,
-        ];
-
-        let buf4 = [
-            0x55, 0x24, 0x04, 0xFF, 0x09, 0x03, 0x04, 0x00, 0x40, 0x48, 0x03, 0x09, 0x01, 0x03,
-            0x00, 0x02, 0xA7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00, 0x66, 0x3E, 0x3E, 0x4C,
-            0x03, 0x00, 0x02, 0x00, 0x32, 0x00, 0xFF, 0xFF,
-        ];
-
-        let packet1 = RMWireFrameView::new(&buf1);
-        let packet2 = RMWireFrameView::new(&buf2);
-        let packet3 = RMWireFrameView::new(&buf3);
-        let packet4 = RMWireFrameView::new(&buf4);
+        use crate::frames::{
+            ROBOSTACK_ADD_NODE, ROBOSTACK_ADD_NODE_AGAIN, ROBOSTACK_ADD_SUB_BATTERY,
+            ROBOSTACK_ADD_SUB_TURRET,
+        };
+
+        let packet1 = RMWireFrameView::new(&ROBOSTACK_ADD_NODE[..]);
+        let packet2 = RMWireFrameView::new(&ROBOSTACK_ADD_SUB_BATTERY[..]);
+        let packet3 = RMWireFrameView::new(&ROBOSTACK_ADD_NODE_AGAIN[..]);
+        let packet4 = RMWireFrameView::new(&ROBOSTACK_ADD_SUB_TURRET[..]);
+        for packet in [&packet1, &packet2, &packet3, &packet4] {
+            assert!(packet.is_valid());
+        }
         std::println!("packet4: {:02x?}", packet4);
 
-        let init2_view = RMAddSubView::new(packet2);
-        let init4_view = RMAddSubView::new(packet4);
+        let init2_view = RMAddSubView::try_new(packet2).unwrap();
+        let init4_view = RMAddSubView::try_new(packet4).unwrap();
 
         assert_eq!(packet1.cmd_set(), CMDSET_VBUS);
         assert_eq!(packet1.cmd_id(), 0x01);
@@ -343,6 +570,107 @@ mod test {
         );
     }
 
+    /// Rebuild a parsed subscription request
+    fn rebuild<'b>(view: &RMAddSubView<&[u8]>, buf: &'b mut [u8]) -> RMAddSubView<&'b mut [u8]> {
+        let header = RMWireFrameBuilder::new()
+            .sender_id(view.packet.sender_id())
+            .sequence_number(view.packet.sequence_number())
+            .need_ack(view.packet.need_ack())
+            .need_ack_after_exec(view.packet.need_ack_after_exec());
+
+        RMAddSubBuilder::new(view.sub_node_id(), view.sub_stream_id())
+            .timestamp_requested(view.timestamp_requested())
+            .stop_when_disconnected(view.stop_when_disconnected())
+            .sub_mode(view.sub_mode())
+            .topics(view.topics().unwrap())
+            .frequency(view.frequency())
+            .build_into(&header, buf)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_add_sub_builder() {
+        // Capture with valid CRCs
+        let buf: [u8; 76] = [
+            0x55, 0x4c, 0x04, 0x6c, 0x09, 0x03, 0x8d, 0x04, 0x40, 0x48, 0x03, 0x09, 0x01, 0x03,
+            0x00, 0x07, 0xa7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00, 0x66, 0x3e, 0x3e, 0x4c,
+            0x03, 0x00, 0x02, 0x00, 0xfb, 0xdc, 0xf5, 0xd7, 0x03, 0x00, 0x02, 0x00, 0x09, 0xa3,
+            0x26, 0xe2, 0x03, 0x00, 0x02, 0x00, 0xf4, 0x1d, 0x1c, 0xdc, 0x03, 0x00, 0x02, 0x00,
+            0x42, 0xee, 0x13, 0x1d, 0x03, 0x00, 0x02, 0x00, 0xb3, 0xf7, 0xe6, 0x47, 0x03, 0x00,
+            0x02, 0x00, 0x32, 0x00, 0x54, 0xbb,
+        ];
+
+        let view = RMAddSubView::try_new(RMWireFrameView::new(&buf[..])).unwrap();
+        let mut out = [0u8; 128];
+        let rebuilt = rebuild(&view, &mut out);
+        assert_eq!(rebuilt.packet.as_bytes(), &buf);
+
+        // RoboStack captures with the CRCs blanked out
+        let captures: [&[u8]; 3] = [
+            &[
+                0x55, 0x1C, 0x04, 0xFF, 0x09, 0x03, 0x02, 0x00, 0x40, 0x48, 0x03, 0x09, 0x00, 0x03,
+                0x00, 0x01, 0xFB, 0xDC, 0xF5, 0xD7, 0x03, 0x00, 0x02, 0x00, 0x01, 0x00, 0xFF, 0xFF,
+            ],
+            &[
+                0x55, 0x24, 0x04, 0xFF, 0x09, 0x03, 0x04, 0x00, 0x40, 0x48, 0x03, 0x09, 0x01, 0x03,
+                0x00, 0x02, 0xA7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00, 0x66, 0x3E, 0x3E, 0x4C,
+                0x03, 0x00, 0x02, 0x00, 0x32, 0x00, 0xFF, 0xFF,
+            ],
+            &[
+                0x55, 0x3C, 0x04, 0xFF, 0x09, 0x03, 0x0F, 0x00, 0x40, 0x48, 0x03, 0x09, 0x02, 0x03,
+                0x00, 0x05, 0x09, 0xA3, 0x26, 0xE2, 0x03, 0x00, 0x02, 0x00, 0xB3, 0xF7, 0xE6, 0x47,
+                0x03, 0x00, 0x02, 0x00, 0xF4, 0x1D, 0x1C, 0xDC, 0x03, 0x00, 0x02, 0x00, 0x03, 0xC5,
+                0x58, 0x08, 0x03, 0x00, 0x02, 0x00, 0x42, 0xEE, 0x13, 0x1D, 0x03, 0x00, 0x02, 0x00,
+                0x05, 0x00, 0xFF, 0xFF,
+            ],
+        ];
+
+        for capture in captures {
+            let view = RMAddSubView::new(RMWireFrameView::new(capture));
+            let rebuilt = rebuild(&view, &mut out);
+            let bytes = rebuilt.packet.as_bytes();
+
+            assert!(rebuilt.packet.is_valid());
+            assert!(RMAddSubView::try_new(RMWireFrameView::new(bytes)).is_ok());
+            assert_eq!(bytes.len(), capture.len());
+            assert_eq!(bytes[..3], capture[..3]);
+            assert_eq!(bytes[4..bytes.len() - 2], capture[4..capture.len() - 2]);
+        }
+    }
+
+    #[test]
+    fn test_add_sub_mutators() {
+        let topics = [VBusUid {
+            uid: crate::duss::vbus::topics::VBUS_BATTERY_STATS,
+        }];
+        let mut buf = [0u8; 32];
+        let mut view = RMAddSubBuilder::new(0x09, 0)
+            .topics(&topics)
+            .frequency(1)
+            .build_into(&RMWireFrameBuilder::new().sender_id(0x09), &mut buf)
+            .unwrap();
+
+        assert!(!view.timestamp_requested());
+        view.set_sub_stream_id(3);
+        view.set_timestamp_requested(true);
+        view.set_stop_when_disconnected(true);
+        view.set_frequency(50);
+        view.topics_mut().unwrap()[0].uid = crate::duss::vbus::topics::VBUS_IMU_DATA;
+
+        assert_eq!(view.sub_stream_id(), 3);
+        assert!(view.timestamp_requested());
+        assert!(view.stop_when_disconnected());
+        assert_eq!(view.frequency(), 50);
+        assert_eq!(
+            view.topics().unwrap()[0].uid,
+            crate::duss::vbus::topics::VBUS_IMU_DATA
+        );
+
+        view.set_stop_when_disconnected(false);
+        assert!(view.timestamp_requested());
+        assert!(!view.stop_when_disconnected());
+    }
+
     #[test]
     fn test_rm_init_packet_1() {
         let buf = [
@@ -7,9 +7,37 @@
 //!
 //! # SDK Topics (HDVT -> SDK)
 //!
-//! These topics are available in the Python SDK code (open source). However, we cannot use these topics in the low-level CAN bus communication.
-//! One must first enter SDK mode to receive these topics. These topics are for the SDK to receive data from the HDVT.
-//! Most likely, we cannot use these on the CAN bus.
+//! These topics are available in the Python SDK code (open source) and defined here as `SDK_*`.
+//! One must first enter SDK mode to receive these topics. These topics are for the SDK to receive data from the HDVT,
+//! so the ADD_SUB request goes to the HDVT instead of the FC:
+//!
+//! ```
+//! use robomaster_s1_proto::{
+//!     address::RMS1Address,
+//!     duss::vbus::{
+//!         topic_view::RMAddSubBuilder,
+//!         topics::{VBusUid, SDK_BATTERY_INFO, SDK_GIMBAL_POS},
+//!     },
+//!     wire::RMWireFrameBuilder,
+//! };
+//!
+//! let topics = [
+//!     VBusUid { uid: SDK_GIMBAL_POS },
+//!     VBusUid { uid: SDK_BATTERY_INFO },
+//! ];
+//! let header = RMWireFrameBuilder::new()
+//!     .sender_id(0x02)
+//!     .receiver(RMS1Address::hdvt_uav_id)
+//!     .need_ack(true);
+//!
+//! let mut buf = [0u8; 64];
+//! let frame = RMAddSubBuilder::new(0x02, 0)
+//!     .topics(&topics)
+//!     .frequency(10)
+//!     .build_into(&header, &mut buf)
+//!     .unwrap();
+//! assert_eq!(frame.packet.receiver_id(), 0x09);
+//! ```
 //!
 //! Low 32-bits:
 //! - 0xc14cb7c5,  # esc_info
@@ -28,16 +56,116 @@
 //! - This is different from the CAN bus topics where the low 32-bits are 0x20003 (0x03, 0x00, 0x02, 0x00)
 //! - Most likely, 0x09 means this topic UUID is on the HDVT
 //! - Similarly, 0x03 means this topic UUID is on the FC
+//!
+//! # Catalog
+//!
+//! [`TOPICS`] lists the known topics with their name, host, size and decoder. Use [`lookup`]
+//! or [`lookup_name`] to find one.
 
 use bytemuck::{Pod, Zeroable};
 
-#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+use crate::address::RMS1Address;
+
+use super::topic_data::{
+    Attitude, BatteryInfo, ChassisStatus, EscInfo, GimbalAngles, ImuData, Position, TopicData,
+    TopicSample, Velocity,
+};
+
+/// Topic UID
+///
+/// Little-endian on the wire: the low 32 bits are the topic ID, the high 32 bits the
+/// publishing host, e.g. 0x20003 for the FC. Ordered by host, then topic ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Pod, Zeroable)]
 #[repr(C)]
 pub struct VBusUid {
     pub uid: [u8; 8],
 }
 
-pub const VBUS_TURRET_YAW: [u8; 8] = [0xa7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00]; // Turret Yaw
+/// Error returned when parsing a [`VBusUid`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseUidError {
+    /// Neither `host:id` in hex nor the name of a known topic
+    Invalid,
+}
+
+impl VBusUid {
+    pub const fn new(host: u32, topic_id: u32) -> Self {
+        let [l0, l1, l2, l3] = topic_id.to_le_bytes();
+        let [h0, h1, h2, h3] = host.to_le_bytes();
+        VBusUid {
+            uid: [l0, l1, l2, l3, h0, h1, h2, h3],
+        }
+    }
+
+    /// Publishing host, the high 32 bits
+    pub const fn host(&self) -> u32 {
+        let [_, _, _, _, h0, h1, h2, h3] = self.uid;
+        u32::from_le_bytes([h0, h1, h2, h3])
+    }
+
+    /// Topic ID, the low 32 bits
+    pub const fn topic_id(&self) -> u32 {
+        let [l0, l1, l2, l3, _, _, _, _] = self.uid;
+        u32::from_le_bytes([l0, l1, l2, l3])
+    }
+
+    /// Address of the publishing host, from the low byte of [`Self::host`]
+    pub fn host_address(&self) -> Option<RMS1Address> {
+        RMS1Address::from_pack_id(self.uid[4])
+    }
+
+    /// Entry of the topic in [`TOPICS`], if known
+    pub fn info(&self) -> Option<&'static TopicInfo> {
+        lookup(self)
+    }
+}
+
+impl PartialOrd for VBusUid {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for VBusUid {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.host(), self.topic_id()).cmp(&(other.host(), other.topic_id()))
+    }
+}
+
+/// `host:id` in hex, e.g. `00020003:d7f5dcfb` for [`VBUS_BATTERY_STATS`]
+impl core::fmt::Display for VBusUid {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:08x}:{:08x}", self.host(), self.topic_id())
+    }
+}
+
+/// Parse `host:id` in hex, with or without `0x`, or the name of a topic in [`TOPICS`]
+impl core::str::FromStr for VBusUid {
+    type Err = ParseUidError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(info) = lookup_name(s) {
+            return Ok(info.uid);
+        }
+
+        let hex = |s: &str| {
+            let s = s
+                .strip_prefix("0x")
+                .or_else(|| s.strip_prefix("0X"))
+                .unwrap_or(s);
+            if s.is_empty() || s.starts_with('+') {
+                return Err(ParseUidError::Invalid);
+            }
+            u32::from_str_radix(s, 16).map_err(|_| ParseUidError::Invalid)
+        };
+        let (host, topic_id) = s.split_once(':').ok_or(ParseUidError::Invalid)?;
+
+        Ok(VBusUid::new(hex(host)?, hex(topic_id)?))
+    }
+}
+
+/// Turret yaw, subscribed by RoboStack at 50 Hz. No push has been captured, the layout is unknown.
+pub const VBUS_TURRET_YAW: [u8; 8] = [0xa7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00];
 pub const VBUS_CHASSIS_VELOCITY: [u8; 8] = [0x66, 0x3e, 0x3e, 0x4c, 0x03, 0x00, 0x02, 0x00]; // Velocity
 pub const VBUS_BATTERY_STATS: [u8; 8] = [0xfb, 0xdc, 0xf5, 0xd7, 0x03, 0x00, 0x02, 0x00]; // Battery
 pub const VBUS_ESC_STATE: [u8; 8] = [0x09, 0xa3, 0x26, 0xe2, 0x03, 0x00, 0x02, 0x00]; // ESC state
@@ -49,3 +177,240 @@ pub const VBUS_BASE_POSITION: [u8; 8] = [0xb3, 0xf7, 0xe6, 0x47, 0x03, 0x00, 0x0
 ///
 /// This is an unknown topic. Subscribed by 0x203 along with VBUS_BATTERY_STATS.
 pub const VBUS_UNK_1: [u8; 8] = [0x9d, 0x1a, 0x1c, 0x99, 0x03, 0x00, 0x02, 0x00];
+
+/// [03, c5, 58, 08, 03, 00, 02, 00]
+///
+/// Subscribed by RoboStack along with the ESC state, position, IMU data and attitude.
+/// Most likely the odometry yaw, the layout is unknown.
+pub const VBUS_ODOM_YAW: [u8; 8] = [0x03, 0xc5, 0x58, 0x08, 0x03, 0x00, 0x02, 0x00];
+
+/// SDK topic `esc_info`, same layout as [`VBUS_ESC_STATE`]
+pub const SDK_ESC_INFO: [u8; 8] = [0xc5, 0xb7, 0x4c, 0xc1, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `ns_pos`, same layout as [`VBUS_BASE_POSITION`]
+pub const SDK_NS_POS: [u8; 8] = [0xce, 0xce, 0xb7, 0xee, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `ns_vel`, same layout as [`VBUS_CHASSIS_VELOCITY`]
+pub const SDK_NS_VEL: [u8; 8] = [0x9c, 0x00, 0xa4, 0x49, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `ns_imu`, same layout as [`VBUS_IMU_DATA`]
+pub const SDK_NS_IMU: [u8; 8] = [0x8d, 0x5b, 0x98, 0xa7, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `attitude_info`, the chassis attitude in the layout of [`VBUS_TURRET_ATTITUDE_RPY`]
+pub const SDK_ATTITUDE_INFO: [u8; 8] = [0x06, 0x63, 0x98, 0x6b, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `ns_sa_status`, chassis status flags
+pub const SDK_NS_SA_STATUS: [u8; 8] = [0x55, 0x6d, 0x2c, 0x4a, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `gimbal_pos`, gimbal angles
+pub const SDK_GIMBAL_POS: [u8; 8] = [0x97, 0x3c, 0x9b, 0xf7, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `stick_flag`, the layout is unknown
+pub const SDK_STICK_FLAG: [u8; 8] = [0xfa, 0xa0, 0xe9, 0x55, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `servo_id_in_roboticarm_mode`, the layout is unknown
+pub const SDK_SERVO_ID_IN_ROBOTICARM_MODE: [u8; 8] =
+    [0xe7, 0x59, 0x00, 0x5f, 0x09, 0x00, 0x02, 0x00];
+/// SDK topic `battery_info`, same layout as [`VBUS_BATTERY_STATS`]
+pub const SDK_BATTERY_INFO: [u8; 8] = [0x9f, 0x22, 0x62, 0x68, 0x09, 0x00, 0x02, 0x00];
+
+/// Decode the data of a topic in a push
+pub type TopicDecoder = fn(&[u8]) -> Option<TopicSample<'_>>;
+
+/// Catalog entry of a topic
+#[derive(Debug, Clone, Copy)]
+pub struct TopicInfo {
+    pub uid: VBusUid,
+    pub name: &'static str,
+    /// Host publishing the topic, from the high 32 bits of the UID
+    pub host: RMS1Address,
+    /// Length of the topic in the push data, `None` if unknown
+    pub size: Option<usize>,
+    pub decode: Option<TopicDecoder>,
+}
+
+impl TopicInfo {
+    const fn unknown(uid: [u8; 8], name: &'static str, host: RMS1Address) -> Self {
+        TopicInfo {
+            uid: VBusUid { uid },
+            name,
+            host,
+            size: None,
+            decode: None,
+        }
+    }
+
+    const fn known<T: TopicData>(
+        uid: [u8; 8],
+        name: &'static str,
+        host: RMS1Address,
+        decode: TopicDecoder,
+    ) -> Self {
+        TopicInfo {
+            uid: VBusUid { uid },
+            name,
+            host,
+            size: Some(T::LEN),
+            decode: Some(decode),
+        }
+    }
+}
+
+const DECODE_VELOCITY: TopicDecoder = |data| Velocity::from_bytes(data).map(TopicSample::Velocity);
+const DECODE_BATTERY: TopicDecoder = |data| BatteryInfo::from_bytes(data).map(TopicSample::Battery);
+const DECODE_ESC: TopicDecoder = |data| EscInfo::from_bytes(data).map(TopicSample::Esc);
+const DECODE_IMU: TopicDecoder = |data| ImuData::from_bytes(data).map(TopicSample::Imu);
+const DECODE_ATTITUDE: TopicDecoder = |data| Attitude::from_bytes(data).map(TopicSample::Attitude);
+const DECODE_POSITION: TopicDecoder = |data| Position::from_bytes(data).map(TopicSample::Position);
+const DECODE_CHASSIS_STATUS: TopicDecoder =
+    |data| ChassisStatus::from_bytes(data).map(TopicSample::ChassisStatus);
+const DECODE_GIMBAL_ANGLES: TopicDecoder =
+    |data| GimbalAngles::from_bytes(data).map(TopicSample::GimbalAngles);
+
+const FC: RMS1Address = RMS1Address::fc_id;
+const HDVT: RMS1Address = RMS1Address::hdvt_uav_id;
+
+/// All the known topics
+pub static TOPICS: [TopicInfo; 19] = [
+    TopicInfo::unknown(VBUS_TURRET_YAW, "turret_yaw", FC),
+    TopicInfo::known::<Velocity>(VBUS_CHASSIS_VELOCITY, "velocity", FC, DECODE_VELOCITY),
+    TopicInfo::known::<BatteryInfo>(VBUS_BATTERY_STATS, "battery", FC, DECODE_BATTERY),
+    TopicInfo::known::<EscInfo>(VBUS_ESC_STATE, "esc_state", FC, DECODE_ESC),
+    TopicInfo::known::<ImuData>(VBUS_IMU_DATA, "imu", FC, DECODE_IMU),
+    TopicInfo::known::<Attitude>(VBUS_TURRET_ATTITUDE_RPY, "attitude", FC, DECODE_ATTITUDE),
+    TopicInfo::known::<Position>(VBUS_BASE_POSITION, "position", FC, DECODE_POSITION),
+    TopicInfo::unknown(VBUS_UNK_1, "unk_1", FC),
+    TopicInfo::unknown(VBUS_ODOM_YAW, "odom_yaw", FC),
+    // SDK topics, named as in the Python SDK
+    TopicInfo::known::<EscInfo>(SDK_ESC_INFO, "esc_info", HDVT, DECODE_ESC),
+    TopicInfo::known::<Position>(SDK_NS_POS, "ns_pos", HDVT, DECODE_POSITION),
+    TopicInfo::known::<Velocity>(SDK_NS_VEL, "ns_vel", HDVT, DECODE_VELOCITY),
+    TopicInfo::known::<ImuData>(SDK_NS_IMU, "ns_imu", HDVT, DECODE_IMU),
+    TopicInfo::known::<Attitude>(SDK_ATTITUDE_INFO, "attitude_info", HDVT, DECODE_ATTITUDE),
+    TopicInfo::known::<ChassisStatus>(
+        SDK_NS_SA_STATUS,
+        "ns_sa_status",
+        HDVT,
+        DECODE_CHASSIS_STATUS,
+    ),
+    TopicInfo::known::<GimbalAngles>(SDK_GIMBAL_POS, "gimbal_pos", HDVT, DECODE_GIMBAL_ANGLES),
+    TopicInfo::unknown(SDK_STICK_FLAG, "stick_flag", HDVT),
+    TopicInfo::unknown(
+        SDK_SERVO_ID_IN_ROBOTICARM_MODE,
+        "servo_id_in_roboticarm_mode",
+        HDVT,
+    ),
+    TopicInfo::known::<BatteryInfo>(SDK_BATTERY_INFO, "battery_info", HDVT, DECODE_BATTERY),
+];
+
+/// Catalog entry of the topic with the given UID
+pub fn lookup(uid: &VBusUid) -> Option<&'static TopicInfo> {
+    TOPICS.iter().find(|info| info.uid == *uid)
+}
+
+/// Catalog entry of the topic with the given name
+pub fn lookup_name(name: &str) -> Option<&'static TopicInfo> {
+    TOPICS.iter().find(|info| info.name == name)
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn test_catalog() {
+        let battery = lookup(&VBusUid {
+            uid: VBUS_BATTERY_STATS,
+        })
+        .unwrap();
+        assert_eq!(battery.name, "battery");
+        assert_eq!(battery.size, Some(10));
+        assert_eq!(lookup_name("battery").unwrap().uid, battery.uid);
+
+        let data = [0xac, 0x2e, 0xdd, 0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00];
+        match (battery.decode.unwrap())(&data) {
+            Some(TopicSample::Battery(info)) => assert_eq!(info.voltage_mv(), 11948),
+            other => panic!("unexpected sample {:?}", other),
+        }
+        assert!((battery.decode.unwrap())(&data[1..]).is_none());
+
+        let odom = VBusUid { uid: VBUS_ODOM_YAW };
+        assert_eq!(odom.info().unwrap().name, "odom_yaw");
+        assert!(odom.info().unwrap().decode.is_none());
+        assert!(lookup_name("nope").is_none());
+        assert!(lookup(&VBusUid { uid: [0; 8] }).is_none());
+
+        for info in TOPICS.iter() {
+            assert_eq!(info.uid.host_address(), Some(info.host));
+            assert_eq!(lookup(&info.uid).unwrap().name, info.name);
+        }
+    }
+
+    #[test]
+    fn test_sdk_topics() {
+        let battery = lookup_name("battery_info").unwrap();
+        assert_eq!(battery.uid.uid, SDK_BATTERY_INFO);
+        assert_eq!(battery.host, RMS1Address::hdvt_uav_id);
+        assert_eq!(battery.uid.to_string(), "00020009:6862229f");
+
+        let data = [0xac, 0x2e, 0xdd, 0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00];
+        assert!(matches!(
+            (battery.decode.unwrap())(&data),
+            Some(TopicSample::Battery(_))
+        ));
+
+        let gimbal = lookup(&VBusUid {
+            uid: SDK_GIMBAL_POS,
+        })
+        .unwrap();
+        assert_eq!(gimbal.name, "gimbal_pos");
+        assert_eq!(gimbal.size, Some(8));
+        assert!(lookup_name("stick_flag").unwrap().size.is_none());
+    }
+
+    #[test]
+    fn test_uid_display() {
+        let uid = VBusUid {
+            uid: VBUS_BATTERY_STATS,
+        };
+        assert_eq!(uid.to_string(), "00020003:d7f5dcfb");
+    }
+
+    #[test]
+    fn test_uid_fields() {
+        let uid = VBusUid::new(0x20003, 0xd7f5dcfb);
+        assert_eq!(uid.uid, VBUS_BATTERY_STATS);
+        assert_eq!(uid.host(), 0x20003);
+        assert_eq!(uid.topic_id(), 0xd7f5dcfb);
+        assert_eq!(uid.host_address(), Some(RMS1Address::fc_id));
+
+        const GIMBAL_POS: VBusUid = VBusUid::new(0x20009, 0xf79b3c97);
+        assert_eq!(GIMBAL_POS.uid, SDK_GIMBAL_POS);
+        assert_eq!(GIMBAL_POS.host_address(), Some(RMS1Address::hdvt_uav_id));
+
+        // Host first, then topic ID
+        assert!(VBusUid::new(0x20003, 0xffff_ffff) < VBusUid::new(0x20009, 0));
+        assert!(VBusUid::new(0x20003, 1) < VBusUid::new(0x20003, 2));
+        assert!(VBusUid::new(0x20003, 0x100) > VBusUid::new(0x20003, 0x01));
+    }
+
+    #[test]
+    fn test_uid_from_str() {
+        let battery = VBusUid {
+            uid: VBUS_BATTERY_STATS,
+        };
+        assert_eq!("00020003:d7f5dcfb".parse(), Ok(battery));
+        assert_eq!("0x20003:0xD7F5DCFB".parse(), Ok(battery));
+        assert_eq!("battery".parse(), Ok(battery));
+        assert_eq!(battery.to_string().parse(), Ok(battery));
+
+        for s in [
+            "",
+            "battery2",
+            "20003",
+            "20003:",
+            ":1",
+            "20003:+1",
+            "1:100000000",
+            "x:1",
+        ] {
+            assert_eq!(s.parse::<VBusUid>(), Err(ParseUidError::Invalid), "{:?}", s);
+        }
+    }
+}
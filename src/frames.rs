@@ -0,0 +1,85 @@
+//! Fixed frames sent by RoboStack
+//!
+//! These frames never change, so they are built at compile time with
+//! [`RMWireFrameBuilder::build_const`] and can be stored in flash. Their bytes match the
+//! RoboStack captures, with the CRCs computed.
+
+use crate::{
+    duss::{
+        cmd_set_gimbal::GimbalCommandType,
+        cmd_set_rm::RMCommandType,
+        cmd_set_types::CommandSetType,
+        vbus::{CMDID_VBUS_ADD_NODE, CMDID_VBUS_ADD_SUB, CMDSET_VBUS},
+    },
+    wire::RMWireFrameBuilder,
+};
+
+/// Enter the slow chassis mode (SET_CHASSIS_SPEED)
+pub const SLOW_MODE_ENTER: [u8; 14] = RMWireFrameBuilder::new()
+    .sender_id(0x09)
+    .receiver_id(0xC3)
+    .sequence_number(0xFFFF)
+    .need_ack_after_exec(true)
+    .cmd_set(CommandSetType::RM as u8)
+    .cmd_id(RMCommandType::SET_CHASSIS_SPEED as u8)
+    .payload(&[0x03])
+    .build_const();
+
+/// Set the gimbal to the free mode (GIMBAL_SET_MODE)
+pub const GIMBAL_MODE_FREE: [u8; 15] = RMWireFrameBuilder::new()
+    .sender_id(0x09)
+    .receiver_id(0xC3)
+    .sequence_number(0xE0)
+    .cmd_set(CommandSetType::GIMBAL as u8)
+    .cmd_id(GimbalCommandType::GIMBAL_SET_MODE as u8)
+    .payload(&[0x00, 0x00])
+    .build_const();
+
+/// First RoboStack init frame: register node 0x09 with the FC
+pub const ROBOSTACK_ADD_NODE: [u8; 18] = RMWireFrameBuilder::new()
+    .sender_id(0x09)
+    .receiver_id(0x03)
+    .sequence_number(0x0001)
+    .need_ack_after_exec(true)
+    .cmd_set(CMDSET_VBUS)
+    .cmd_id(CMDID_VBUS_ADD_NODE)
+    .payload(&[0x09, 0x00, 0x00, 0x00, 0x03])
+    .build_const();
+
+/// Second RoboStack init frame: subscribe to the battery topic at 1 Hz on stream 0
+pub const ROBOSTACK_ADD_SUB_BATTERY: [u8; 28] = RMWireFrameBuilder::new()
+    .sender_id(0x09)
+    .receiver_id(0x03)
+    .sequence_number(0x0002)
+    .need_ack_after_exec(true)
+    .cmd_set(CMDSET_VBUS)
+    .cmd_id(CMDID_VBUS_ADD_SUB)
+    .payload(&[
+        0x09, 0x00, 0x03, 0x00, 0x01, 0xFB, 0xDC, 0xF5, 0xD7, 0x03, 0x00, 0x02, 0x00, 0x01, 0x00,
+    ])
+    .build_const();
+
+/// Third RoboStack init frame: register node 0x09 again
+pub const ROBOSTACK_ADD_NODE_AGAIN: [u8; 18] = RMWireFrameBuilder::new()
+    .sender_id(0x09)
+    .receiver_id(0x03)
+    .sequence_number(0x0003)
+    .need_ack_after_exec(true)
+    .cmd_set(CMDSET_VBUS)
+    .cmd_id(CMDID_VBUS_ADD_NODE)
+    .payload(&[0x09, 0x00, 0x00, 0x00, 0x03])
+    .build_const();
+
+/// Fourth RoboStack init frame: subscribe to the turret yaw and the velocity at 50 Hz on stream 1
+pub const ROBOSTACK_ADD_SUB_TURRET: [u8; 36] = RMWireFrameBuilder::new()
+    .sender_id(0x09)
+    .receiver_id(0x03)
+    .sequence_number(0x0004)
+    .need_ack_after_exec(true)
+    .cmd_set(CMDSET_VBUS)
+    .cmd_id(CMDID_VBUS_ADD_SUB)
+    .payload(&[
+        0x09, 0x01, 0x03, 0x00, 0x02, 0xA7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00, 0x66, 0x3E,
+        0x3E, 0x4C, 0x03, 0x00, 0x02, 0x00, 0x32, 0x00,
+    ])
+    .build_const();
@@ -1,9 +1,17 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod address;
+pub mod cipher;
 pub mod commands;
 pub mod crc;
 pub mod duss;
+pub mod frames;
+pub mod message;
 pub mod proto;
+pub mod routing;
+pub mod transaction;
 pub mod wire;
@@ -0,0 +1,340 @@
+//! Typed decoding of RM-S1 frames
+//!
+//! [`decode`] dispatches on the (cmd_set, cmd_id) of a frame and returns the matching command view.
+//!
+//! Commands that have been captured but whose payload layout is not known, such as
+//! `ARMOR_LED_SET`, `SHOOT_CMD` or `GIMBAL_SET_VEL`, are returned as [`Message::Unknown`].
+
+use crate::{
+    commands::{
+        chassis::{
+            speed::{ChassisSpeedModeSetView, SpeedSetView},
+            work_mode::ChassisWorkModeSetView,
+        },
+        gimbal::{degree_ctrl::GimbalSetDegreeView, work_mode::GimbalWorkModeSetView},
+        rmc::RMCPacketView,
+    },
+    duss::{
+        cmd_set_gimbal::GimbalCommandType,
+        cmd_set_rm::RMCommandType,
+        cmd_set_types::CommandSetType,
+        vbus::{
+            cmd_view::{
+                RMAddNodeAckView, RMAddNodeView, RMAddSubAckView, RMDelSubView, RMNodeResetView,
+                RMVBusAckView,
+            },
+            topic_view::{RMAddSubView, RMTopicView},
+            CMDID_VBUS_ADD_NODE, CMDID_VBUS_ADD_SUB, CMDID_VBUS_DEL_SUB, CMDID_VBUS_PUSH_MSG,
+            CMDID_VBUS_RESET_NODE, CMDSET_VBUS,
+        },
+    },
+    wire::{FrameError, RMWireFrameView},
+};
+
+const CMDSET_RM: u8 = CommandSetType::RM as u8;
+const CMDSET_GIMBAL: u8 = CommandSetType::GIMBAL as u8;
+
+const CMDID_SPEED_SET: u8 = RMCommandType::SPEED_SET as u8;
+const CMDID_WORK_MODE_SET: u8 = RMCommandType::WORK_MODE_SET as u8;
+const CMDID_SET_CHASSIS_SPEED: u8 = RMCommandType::SET_CHASSIS_SPEED as u8;
+const CMDID_FC_RMC: u8 = RMCommandType::FC_RMC as u8;
+const CMDID_GIMBAL_DEGREE_SET: u8 = RMCommandType::GIMBAL_DEGREE_SET as u8;
+const CMDID_GIMBAL_SET_MODE: u8 = GimbalCommandType::GIMBAL_SET_MODE as u8;
+
+/// Direction of a frame
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    Request,
+    Ack,
+}
+
+/// A decoded RM-S1 message
+#[derive(Debug)]
+pub enum Message<T: AsRef<[u8]>> {
+    SpeedSet(SpeedSetView<T>),
+    ChassisSpeedModeSet(ChassisSpeedModeSetView<T>),
+    ChassisWorkModeSet(ChassisWorkModeSetView<T>),
+    RMC(RMCPacketView<T>),
+    GimbalSetDegree(GimbalSetDegreeView<T>),
+    GimbalWorkModeSet(GimbalWorkModeSetView<T>),
+    VBusAddNode(RMAddNodeView<T>),
+    VBusNodeReset(RMNodeResetView<T>),
+    VBusAddSub(RMAddSubView<T>),
+    VBusDelSub(RMDelSubView<T>),
+    VBusPush(RMTopicView<T>),
+    VBusAddNodeAck(RMAddNodeAckView<T>),
+    VBusAddSubAck(RMAddSubAckView<T>),
+    /// ACK of another VBUS request, carrying a return code
+    VBusAck(RMVBusAckView<T>),
+    /// ACK of a request outside VBUS, the payload layout is command specific
+    Ack(RMWireFrameView<T>),
+    /// A valid frame with an unknown (cmd_set, cmd_id) or payload layout
+    Unknown(RMWireFrameView<T>),
+}
+
+impl<T: AsRef<[u8]>> Message<T> {
+    /// The underlying frame
+    pub fn packet(&self) -> &RMWireFrameView<T> {
+        match self {
+            Message::SpeedSet(view) => &view.packet,
+            Message::ChassisSpeedModeSet(view) => &view.packet,
+            Message::ChassisWorkModeSet(view) => &view.packet,
+            Message::RMC(view) => &view.packet,
+            Message::GimbalSetDegree(view) => &view.packet,
+            Message::GimbalWorkModeSet(view) => &view.packet,
+            Message::VBusAddNode(view) => &view.packet,
+            Message::VBusNodeReset(view) => &view.packet,
+            Message::VBusAddSub(view) => &view.packet,
+            Message::VBusDelSub(view) => &view.packet,
+            Message::VBusPush(view) => &view.packet,
+            Message::VBusAddNodeAck(view) => &view.packet,
+            Message::VBusAddSubAck(view) => &view.packet,
+            Message::VBusAck(view) => &view.packet,
+            Message::Ack(packet) => packet,
+            Message::Unknown(packet) => packet,
+        }
+    }
+
+    pub fn direction(&self) -> Direction {
+        if self.packet().is_ack() {
+            Direction::Ack
+        } else {
+            Direction::Request
+        }
+    }
+}
+
+/// Build the view of a frame whose CRCs have already been checked, or
+/// [`Message::Unknown`] if its layout does not match
+macro_rules! typed {
+    ($variant:ident, $view:ident, $frame:expr) => {{
+        match $view::check_layout(&$frame) {
+            Ok(()) => Message::$variant($view::new($frame)),
+            Err(_) => Message::Unknown($frame),
+        }
+    }};
+}
+
+/// Decode a frame into a typed message
+///
+/// The frame is checked once, then matched against the known commands. VBUS ACKs are
+/// returned as their typed views, other ACKs as [`Message::Ack`]. Frames of a known command
+/// with a malformed payload are returned as [`Message::Unknown`]. Only frames failing
+/// [`RMWireFrameView::check`] are rejected.
+///
+/// The experimental VBUS views of [`crate::duss::vbus::cmd_view`] are not used, their
+/// commands are returned as [`Message::Unknown`].
+pub fn decode<T: AsRef<[u8]>>(frame: RMWireFrameView<T>) -> Result<Message<T>, FrameError> {
+    frame.check()?;
+
+    if frame.is_ack() {
+        let message = match (frame.cmd_set(), frame.cmd_id()) {
+            (CMDSET_VBUS, CMDID_VBUS_ADD_NODE) => {
+                typed!(VBusAddNodeAck, RMAddNodeAckView, frame)
+            }
+            (CMDSET_VBUS, CMDID_VBUS_ADD_SUB) => typed!(VBusAddSubAck, RMAddSubAckView, frame),
+            // Pushes are not answered with a return code
+            (CMDSET_VBUS, cmd_id) if cmd_id != CMDID_VBUS_PUSH_MSG => {
+                typed!(VBusAck, RMVBusAckView, frame)
+            }
+            _ => Message::Ack(frame),
+        };
+        return Ok(message);
+    }
+
+    let message = match (frame.cmd_set(), frame.cmd_id()) {
+        (CMDSET_RM, CMDID_SPEED_SET) => typed!(SpeedSet, SpeedSetView, frame),
+        (CMDSET_RM, CMDID_SET_CHASSIS_SPEED) => {
+            typed!(ChassisSpeedModeSet, ChassisSpeedModeSetView, frame)
+        }
+        (CMDSET_RM, CMDID_WORK_MODE_SET) => {
+            typed!(ChassisWorkModeSet, ChassisWorkModeSetView, frame)
+        }
+        (CMDSET_RM, CMDID_FC_RMC) => typed!(RMC, RMCPacketView, frame),
+        (CMDSET_RM, CMDID_GIMBAL_DEGREE_SET) => {
+            typed!(GimbalSetDegree, GimbalSetDegreeView, frame)
+        }
+        (CMDSET_GIMBAL, CMDID_GIMBAL_SET_MODE) => {
+            typed!(GimbalWorkModeSet, GimbalWorkModeSetView, frame)
+        }
+        (CMDSET_VBUS, CMDID_VBUS_ADD_NODE) => typed!(VBusAddNode, RMAddNodeView, frame),
+        (CMDSET_VBUS, CMDID_VBUS_RESET_NODE) => typed!(VBusNodeReset, RMNodeResetView, frame),
+        (CMDSET_VBUS, CMDID_VBUS_ADD_SUB) => typed!(VBusAddSub, RMAddSubView, frame),
+        (CMDSET_VBUS, CMDID_VBUS_DEL_SUB) => typed!(VBusDelSub, RMDelSubView, frame),
+        (CMDSET_VBUS, CMDID_VBUS_PUSH_MSG) => typed!(VBusPush, RMTopicView, frame),
+        _ => Message::Unknown(frame),
+    };
+
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::wire::RMWireFrameBuilder;
+
+    #[test]
+    fn test_decode_speed_set() {
+        let buf = [
+            0x55, 0x19, 0x04, 0xe4, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x3F, 0x21, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x7d, 0x16,
+        ];
+
+        let message = decode(RMWireFrameView::new(&buf)).unwrap();
+        assert_eq!(message.direction(), Direction::Request);
+        match message {
+            Message::SpeedSet(view) => assert_eq!(view.x(), 1.0),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_speed_mode_set() {
+        // `test_slow_mode_enter` in `wire.rs` with its CRCs filled in
+        let mut buf = [0u8; 16];
+        let frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0xC3)
+            .sequence_number(0xFFFF)
+            .need_ack_after_exec(true)
+            .cmd_set(CMDSET_RM)
+            .cmd_id(CMDID_SET_CHASSIS_SPEED)
+            .payload(&[0x03])
+            .build_into(&mut buf)
+            .unwrap();
+
+        match decode(frame).unwrap() {
+            Message::ChassisSpeedModeSet(view) => assert_eq!(view.mode(), 0x03),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_gimbal_work_mode() {
+        let buf = [
+            0x55, 0x0F, 0x04, 0xa2, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x04, 0x4C, 0x00, 0x00, 0x6c,
+            0xe1,
+        ];
+
+        let message = decode(RMWireFrameView::new(&buf)).unwrap();
+        assert!(matches!(message, Message::GimbalWorkModeSet(_)));
+    }
+
+    #[test]
+    fn test_decode_vbus() {
+        let buf: [u8; 76] = [
+            0x55, 0x4c, 0x04, 0x6c, 0x09, 0x03, 0x8d, 0x04, 0x40, 0x48, 0x03, 0x09, 0x01, 0x03,
+            0x00, 0x07, 0xa7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00, 0x66, 0x3e, 0x3e, 0x4c,
+            0x03, 0x00, 0x02, 0x00, 0xfb, 0xdc, 0xf5, 0xd7, 0x03, 0x00, 0x02, 0x00, 0x09, 0xa3,
+            0x26, 0xe2, 0x03, 0x00, 0x02, 0x00, 0xf4, 0x1d, 0x1c, 0xdc, 0x03, 0x00, 0x02, 0x00,
+            0x42, 0xee, 0x13, 0x1d, 0x03, 0x00, 0x02, 0x00, 0xb3, 0xf7, 0xe6, 0x47, 0x03, 0x00,
+            0x02, 0x00, 0x32, 0x00, 0x54, 0xbb,
+        ];
+        let message = decode(RMWireFrameView::new(&buf)).unwrap();
+        assert!(matches!(message, Message::VBusAddSub(_)));
+
+        let buf = [
+            0x55, 0x31, 0x04, 0x53, 0x03, 0x04, 0x8d, 0x04, //
+            0x20, 0x48, 0x08, 0x00, 0x00, 0x99, 0x1d, 0x00, //
+            0x00, 0xf0, 0xa0, 0x73, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0xac, 0x2e, 0xdd, //
+            0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00, 0x3f, //
+            0x41,
+        ];
+        let message = decode(RMWireFrameView::new(&buf)).unwrap();
+        assert!(matches!(message, Message::VBusPush(_)));
+    }
+
+    #[test]
+    fn test_decode_vbus_management() {
+        use crate::duss::vbus::cmd_view::RMDelSubView;
+
+        let mut buf = [0u8; 32];
+        let header = RMWireFrameBuilder::new().sender_id(0x09);
+        let frame = RMDelSubView::build_into(&header, &mut buf, 0, 0x09, 1).unwrap();
+
+        let message = decode(RMWireFrameView::new(frame.packet.as_bytes())).unwrap();
+        match message {
+            Message::VBusDelSub(view) => assert_eq!(view.sub_stream_id(), 1),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_ack_and_unknown() {
+        let buf = [
+            0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+        ];
+        let message = decode(RMWireFrameView::new(&buf)).unwrap();
+        assert_eq!(message.direction(), Direction::Ack);
+        assert!(matches!(message, Message::Ack(_)));
+
+        let buf = [
+            0x55, 0x24, 0x04, 0x40, 0x58, 0x1d, 0x00, 0x00, //
+            0x00, 0x00, 0xf0, 0x00, 0x6d, 0x69, 0x63, 0x3a, //
+            0x68, 0x7a, 0x20, 0x65, 0x72, 0x72, 0x2c, 0x76, //
+            0x61, 0x6c, 0x75, 0x65, 0x3a, 0x35, 0x31, 0x31, //
+            0x0d, 0x0a, 0x41, 0x04,
+        ];
+        let message = decode(RMWireFrameView::new(&buf)).unwrap();
+        assert_eq!(message.packet().cmd_id(), 0xf0);
+        assert!(matches!(message, Message::Unknown(_)));
+    }
+
+    #[test]
+    fn test_decode_vbus_acks() {
+        use crate::duss::vbus::{cmd_view::RMQueryConfView, CMDID_VBUS_QUERY_CONF};
+
+        let mut buf = [0u8; 32];
+        let header = RMWireFrameBuilder::new()
+            .sender_id(0x03)
+            .receiver_id(0x09)
+            .sequence_number(2);
+
+        let ack = RMAddSubAckView::build_into(&header, &mut buf, 0x03, 0, 1).unwrap();
+        match decode(RMWireFrameView::new(ack.packet.as_bytes())).unwrap() {
+            Message::VBusAddSubAck(view) => assert_eq!(view.sub_stream_id(), Some(1)),
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        let ack = RMAddNodeAckView::build_into(&header, &mut buf, 0x03).unwrap();
+        let message = decode(RMWireFrameView::new(ack.packet.as_bytes())).unwrap();
+        assert!(matches!(message, Message::VBusAddNodeAck(_)));
+
+        let ack = RMVBusAckView::build_into(&header, &mut buf, CMDID_VBUS_DEL_SUB, 0x22).unwrap();
+        match decode(RMWireFrameView::new(ack.packet.as_bytes())).unwrap() {
+            Message::VBusAck(view) => assert_eq!(view.retcode(), 0x22),
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        // Experimental views are not decoded
+        let request = RMQueryConfView::build_into(&header, &mut buf, 0x09, 1).unwrap();
+        let message = decode(RMWireFrameView::new(request.packet.as_bytes())).unwrap();
+        assert!(matches!(message, Message::Unknown(_)));
+        assert_eq!(message.packet().cmd_id(), CMDID_VBUS_QUERY_CONF);
+    }
+
+    #[test]
+    fn test_decode_malformed_payload() {
+        let mut buf = [0u8; 32];
+        let frame = RMWireFrameBuilder::new()
+            .cmd_set(CMDSET_RM)
+            .cmd_id(CMDID_SPEED_SET)
+            .payload(&[0x00; 4])
+            .build_into(&mut buf)
+            .unwrap();
+
+        match decode(frame).unwrap() {
+            Message::Unknown(packet) => {
+                assert_eq!(packet.cmd_id(), CMDID_SPEED_SET);
+                assert_eq!(packet.payload(), &[0x00; 4]);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+}
@@ -2,8 +2,15 @@
 //!
 //! The RM-S1 CAN bus is used as a stream bus. Each can frame is just another part of
 //! the stream from one CAN ID.
+//!
+//! Each node sends on its own CAN ID, regardless of the receiver of the frame.
+//! The IDs observed on the bus are listed in [`CAN_ID_TABLE`].
 
-use crate::crc::{rm_s1_crc16, rm_s1_crc8};
+use crate::{
+    address::{hostid2packid, RMS1Address},
+    crc::{rm_s1_crc16, rm_s1_crc8, RM_S1_CRC16_SEED, RM_S1_CRC8_SEED},
+    wire::{RMWireFrameView, SplitBuf, VersionCheck, MIN_FRAME_LEN},
+};
 
 const SOF: u8 = 0x55;
 
@@ -17,6 +24,14 @@ pub enum ParseError {
     NoStartOfFrame,
     InvalidHeaderCRC(usize),
     InvalidPacketCRC(usize),
+    /// The header is valid but the protocol version is not [`crate::wire::RM_S1_VERSION`]
+    ///
+    /// The u8 is the version of the frame, the usize is the number of bytes to skip
+    UnknownVersion(u8, usize),
+    /// The header is valid but the length field is shorter than an empty frame
+    ///
+    /// The usize is the number of bytes to skip
+    InvalidLength(usize),
 }
 
 /// Try to parse a frame from the buffer
@@ -25,6 +40,7 @@ pub enum ParseError {
 /// - Find the start of frame
 /// - Read the frame length
 /// - Check the header CRC
+/// - Check the protocol version
 /// - Check if we have enough data to read the whole frame
 /// - Check the packet CRC
 ///
@@ -32,36 +48,142 @@ pub enum ParseError {
 ///
 /// If the frame is invalid, it will return an error, and the number of bytes that need to be dropped.
 /// This allows the caller to skip the invalid bytes and try to parse the next frame.
+///
+/// Frames with an unknown protocol version are rejected, see [`parse_frame_with`] to change that.
 pub fn parse_frame(buffer: &[u8]) -> Result<(&[u8], usize), ParseError> {
-    let mut idx = 0;
+    parse_frame_with(buffer, VersionCheck::Strict)
+}
 
-    // Find the start of frame
-    while idx < buffer.len() {
-        if buffer[idx] == SOF {
-            break;
-        }
-        idx += 1;
+/// Try to parse a frame from the buffer, with the given version handling
+///
+/// See [`parse_frame`].
+pub fn parse_frame_with(
+    buffer: &[u8],
+    version_check: VersionCheck,
+) -> Result<(&[u8], usize), ParseError> {
+    let (start, end) = find_frame(&buffer, version_check)?;
+    Ok((&buffer[start..end], end))
+}
+
+/// Try to parse a frame from a buffer split in two parts, e.g. a DMA ring buffer that wrapped around
+///
+/// `head` is the older part of the data and `tail` the part that wrapped to the start of the ring.
+/// Returns the frame and the number of bytes consumed, counted over both parts.
+/// No data is copied, the CRCs are computed across the split.
+///
+/// See [`parse_frame`].
+pub fn parse_frame_split<'a>(
+    head: &'a [u8],
+    tail: &'a [u8],
+) -> Result<(SplitBuf<'a>, usize), ParseError> {
+    parse_frame_split_with(head, tail, VersionCheck::Strict)
+}
+
+/// Try to parse a frame from a buffer split in two parts, with the given version handling
+///
+/// See [`parse_frame_split`].
+pub fn parse_frame_split_with<'a>(
+    head: &'a [u8],
+    tail: &'a [u8],
+    version_check: VersionCheck,
+) -> Result<(SplitBuf<'a>, usize), ParseError> {
+    let buffer = SplitBuf::new(head, tail);
+    let (start, end) = find_frame(&buffer, version_check)?;
+    Ok((buffer.slice(start, end), end))
+}
+
+/// Bytes that a frame can be parsed from
+trait FrameBytes {
+    fn len(&self) -> usize;
+    fn byte(&self, index: usize) -> u8;
+    fn find(&self, from: usize, byte: u8) -> Option<usize>;
+    fn crc8(&self, start: usize, end: usize) -> u8;
+    fn crc16(&self, start: usize, end: usize) -> u16;
+}
+
+impl FrameBytes for &[u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn byte(&self, index: usize) -> u8 {
+        self[index]
+    }
+
+    fn find(&self, from: usize, byte: u8) -> Option<usize> {
+        self[from..]
+            .iter()
+            .position(|b| *b == byte)
+            .map(|i| i + from)
+    }
+
+    fn crc8(&self, start: usize, end: usize) -> u8 {
+        rm_s1_crc8(&self[start..end])
+    }
+
+    fn crc16(&self, start: usize, end: usize) -> u16 {
+        rm_s1_crc16(&self[start..end])
+    }
+}
+
+impl FrameBytes for SplitBuf<'_> {
+    fn len(&self) -> usize {
+        SplitBuf::len(self)
+    }
+
+    fn byte(&self, index: usize) -> u8 {
+        self.get(index).unwrap()
     }
 
-    if idx == buffer.len() {
-        return Err(ParseError::NoStartOfFrame);
+    fn find(&self, from: usize, byte: u8) -> Option<usize> {
+        self.slice(from, self.len())
+            .iter()
+            .position(|b| b == byte)
+            .map(|i| i + from)
     }
 
+    fn crc8(&self, start: usize, end: usize) -> u8 {
+        self.slice(start, end).crc8_with(RM_S1_CRC8_SEED)
+    }
+
+    fn crc16(&self, start: usize, end: usize) -> u16 {
+        self.slice(start, end).crc16_with(RM_S1_CRC16_SEED)
+    }
+}
+
+/// Find the next frame, returns its start and end offsets
+fn find_frame<B: FrameBytes>(
+    buffer: &B,
+    version_check: VersionCheck,
+) -> Result<(usize, usize), ParseError> {
+    // Find the start of frame
+    let idx = buffer.find(0, SOF).ok_or(ParseError::NoStartOfFrame)?;
+
     // Check if we have enough data to read the frame header
     if idx + 4 > buffer.len() {
         return Err(ParseError::NeedMoreData(4 - (buffer.len() - idx), idx));
     }
 
     // Read the frame length
-    let frame_len = buffer[idx + 1] as usize | ((buffer[idx + 2] & 0x03) as usize) << 8;
+    let frame_len = buffer.byte(idx + 1) as usize | ((buffer.byte(idx + 2) & 0x03) as usize) << 8;
 
     // Check the header CRC
-    let header_crc = buffer[idx + 3];
-    let header = &buffer[idx..idx + 3];
-    if rm_s1_crc8(header) != header_crc {
+    let header_crc = buffer.byte(idx + 3);
+    if buffer.crc8(idx, idx + 3) != header_crc {
         return Err(ParseError::InvalidHeaderCRC(idx + 3));
     }
 
+    // Check the protocol version
+    let version = buffer.byte(idx + 2) >> 2;
+    if !version_check.accept(version) {
+        return Err(ParseError::UnknownVersion(version, idx + 3));
+    }
+
+    // Check that the length is plausible, only skip the SOF as this is not a real header
+    if frame_len < MIN_FRAME_LEN {
+        return Err(ParseError::InvalidLength(idx + 1));
+    }
+
     // Check if we have enough data to read the whole frame
     if idx + frame_len > buffer.len() {
         return Err(ParseError::NeedMoreData(
@@ -71,14 +193,560 @@ pub fn parse_frame(buffer: &[u8]) -> Result<(&[u8], usize), ParseError> {
     }
 
     // Check the packet CRC
-    let packet_header_body = &buffer[idx..idx + frame_len - 2];
-    let packet_crc = &buffer[idx + frame_len - 2..idx + frame_len];
-    if rm_s1_crc16(packet_header_body) != u16::from_le_bytes([packet_crc[0], packet_crc[1]]) {
+    let packet_crc = u16::from_le_bytes([
+        buffer.byte(idx + frame_len - 2),
+        buffer.byte(idx + frame_len - 1),
+    ]);
+    if buffer.crc16(idx, idx + frame_len - 2) != packet_crc {
         // Skip the header so we can find the next frame
         return Err(ParseError::InvalidPacketCRC(idx + 3));
     }
 
-    Ok((&buffer[idx..idx + frame_len], idx + frame_len))
+    Ok((idx, idx + frame_len))
+}
+
+/// Result of [`resync`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Resync<'a> {
+    /// A valid frame, preceded by `dropped` bytes that are not part of any frame
+    Frame { frame: &'a [u8], dropped: usize },
+    /// No complete frame
+    ///
+    /// The first `dropped` bytes are not part of any frame and can be discarded. The rest of the
+    /// buffer may be the start of a frame, at least `needed` more bytes are required to tell.
+    Incomplete { dropped: usize, needed: usize },
+}
+
+/// Find the next valid frame, trying every SOF candidate
+///
+/// Unlike [`parse_frame`], which skips a fixed number of bytes after an error, this function
+/// checks every `0x55` in the buffer as a potential frame start. A candidate is only committed to
+/// after its header CRC8, version and length have been validated, so a `0x55` inside a payload
+/// or a corrupted byte does not hide the frames that follow it.
+///
+/// # Example
+///
+/// ```
+/// use robomaster_s1_proto::proto::{resync, Resync};
+///
+/// // A stray SOF right before a frame
+/// let buffer = [
+///     0x55, 0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+/// ];
+///
+/// match resync(&buffer) {
+///     Resync::Frame { frame, dropped } => {
+///         assert_eq!(dropped, 1);
+///         assert_eq!(frame, &buffer[1..]);
+///     }
+///     Resync::Incomplete { .. } => unreachable!(),
+/// }
+/// ```
+pub fn resync(buffer: &[u8]) -> Resync<'_> {
+    resync_with(buffer, VersionCheck::Strict)
+}
+
+/// Find the next valid frame, trying every SOF candidate, with the given version handling
+///
+/// See [`resync`].
+pub fn resync_with(buffer: &[u8], version_check: VersionCheck) -> Resync<'_> {
+    let mut idx = 0;
+    while let Some(offset) = buffer[idx..].iter().position(|b| *b == SOF) {
+        let start = idx + offset;
+        match find_frame(&&buffer[start..], version_check) {
+            Ok((_, end)) => {
+                return Resync::Frame {
+                    frame: &buffer[start..start + end],
+                    dropped: start,
+                }
+            }
+            Err(ParseError::NeedMoreData(needed, _)) => {
+                return Resync::Incomplete {
+                    dropped: start,
+                    needed,
+                }
+            }
+            Err(_) => idx = start + 1,
+        }
+    }
+
+    Resync::Incomplete {
+        dropped: buffer.len(),
+        needed: MIN_FRAME_LEN,
+    }
+}
+
+/// Iterator over the frames in a contiguous buffer
+///
+/// Created by [`frames`].
+#[derive(Debug, Clone)]
+pub struct Frames<'a> {
+    buffer: &'a [u8],
+    offset: usize,
+    done: bool,
+}
+
+impl<'a> Frames<'a> {
+    /// Offset of the first byte that has not been consumed yet
+    ///
+    /// After a [`ParseError::NeedMoreData`], this is the start of the trailing partial frame.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The bytes that have not been consumed yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.buffer[self.offset..]
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<RMWireFrameView<&'a [u8]>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.offset >= self.buffer.len() {
+            return None;
+        }
+
+        let buffer = self.buffer;
+        match parse_frame(&buffer[self.offset..]) {
+            Ok((frame, consumed)) => {
+                self.offset += consumed;
+                Some(Ok(RMWireFrameView::new(frame)))
+            }
+            Err(ParseError::NeedMoreData(needed, consumed)) => {
+                self.offset += consumed;
+                self.done = true;
+                Some(Err(ParseError::NeedMoreData(needed, consumed)))
+            }
+            Err(ParseError::NoStartOfFrame) => {
+                self.offset = buffer.len();
+                self.done = true;
+                None
+            }
+            Err(
+                e @ (ParseError::InvalidHeaderCRC(consumed)
+                | ParseError::InvalidPacketCRC(consumed)
+                | ParseError::UnknownVersion(_, consumed)
+                | ParseError::InvalidLength(consumed)),
+            ) => {
+                self.offset += consumed;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for Frames<'_> {}
+
+/// Iterate over all frames in `buffer`
+///
+/// Invalid frames are reported as errors and skipped, the iterator then resynchronises on the next SOF.
+/// A frame cut off at the end of the buffer is reported as [`ParseError::NeedMoreData`] and ends the iteration,
+/// see [`Frames::offset`] for where it starts. Bytes without a SOF are skipped silently.
+///
+/// # Example
+///
+/// ```
+/// use robomaster_s1_proto::proto::frames;
+///
+/// let buffer = [
+///     0x00, 0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+/// ];
+///
+/// let mut iter = frames(&buffer);
+/// assert_eq!(iter.next().unwrap().unwrap().cmd_set(), 0x48);
+/// assert!(iter.next().is_none());
+/// ```
+pub fn frames(buffer: &[u8]) -> Frames<'_> {
+    Frames {
+        buffer,
+        offset: 0,
+        done: false,
+    }
+}
+
+/// Maximum number of data bytes in a classic CAN frame
+pub const CAN_MAX_DLEN: usize = 8;
+
+/// Packet ID (short address) to CAN ID of the node
+///
+/// The motion controller hosts both the FC (0x03) and the chassis (0xC3) and sends on a single CAN ID.
+///
+/// Only the HDVT, motion controller, gimbal and armor 2 entries are backed by captures. The gun
+/// and the other armors are unverified, their CAN IDs are extrapolated from armor 2 and the
+/// numbering of the other nodes.
+pub const CAN_ID_TABLE: [(u8, u32); 11] = [
+    (hostid2packid(RMS1Address::hdvt_uav_id as u16), 0x201),
+    // FC
+    (0x03, 0x202),
+    (hostid2packid(RMS1Address::chassis_id as u16), 0x202),
+    (hostid2packid(RMS1Address::gimbal_id as u16), 0x203),
+    // Unverified
+    (hostid2packid(RMS1Address::gun_id as u16), 0x204),
+    // Unverified
+    (hostid2packid(RMS1Address::armor1_id as u16), 0x211),
+    // See `test_rm_crc16` in `crc.rs`
+    (hostid2packid(RMS1Address::armor2_id as u16), 0x212),
+    // Unverified
+    (hostid2packid(RMS1Address::armor3_id as u16), 0x213),
+    (hostid2packid(RMS1Address::armor4_id as u16), 0x214),
+    (hostid2packid(RMS1Address::armor5_id as u16), 0x215),
+    (hostid2packid(RMS1Address::armor6_id as u16), 0x216),
+];
+
+/// Look up the CAN ID a node sends on from its packet ID
+pub fn can_id_for_pack_id(pack_id: u8) -> Option<u32> {
+    CAN_ID_TABLE
+        .iter()
+        .find(|(id, _)| *id == pack_id)
+        .map(|(_, can_id)| *can_id)
+}
+
+/// Iterator over the CAN frames of one wire frame
+///
+/// Yields `(CAN ID, data)` pairs, each data slice holds at most [`CAN_MAX_DLEN`] bytes.
+#[derive(Debug, Clone)]
+pub struct Segments<'a> {
+    can_id: u32,
+    chunks: core::slice::Chunks<'a, u8>,
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = (u32, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|chunk| (self.can_id, chunk))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Segments<'_> {}
+
+/// Split a frame into CAN frames sent on `can_id`
+pub fn segment(can_id: u32, frame: &[u8]) -> Segments<'_> {
+    Segments {
+        can_id,
+        chunks: frame.chunks(CAN_MAX_DLEN),
+    }
+}
+
+/// Split a frame into CAN frames sent on the CAN ID of its sender
+///
+/// Returns `None` if the sender is not in [`CAN_ID_TABLE`].
+pub fn segment_frame<T: AsRef<[u8]>>(frame: &RMWireFrameView<T>) -> Option<Segments<'_>> {
+    let can_id = can_id_for_pack_id(frame.sender_id())?;
+    Some(segment(can_id, frame.as_bytes()))
+}
+
+/// Errors returned by [`StreamReassembler::push`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReassemblyError {
+    /// More than [`CAN_MAX_DLEN`] bytes were pushed at once
+    DataTooLong(usize),
+    /// All stream slots are taken by other CAN IDs
+    NoFreeStream,
+    /// The stream buffer is full, the buffered bytes of the stream were discarded
+    BufferOverflow,
+}
+
+/// Result of scanning a stream buffer for the next frame
+enum Scan {
+    /// A valid frame was found at `start..end`
+    Frame(usize, usize),
+    /// The frame is incomplete, drop the first usize bytes and wait until the buffer holds the second usize bytes
+    NeedMoreData(usize, usize),
+}
+
+/// Same as [`resync`], over a buffer that may be split in two parts
+fn scan(buffer: SplitBuf<'_>) -> Scan {
+    let mut idx = 0;
+    while let Some(start) = FrameBytes::find(&buffer, idx, SOF) {
+        match find_frame(&buffer.slice(start, buffer.len()), VersionCheck::Strict) {
+            Ok((_, end)) => return Scan::Frame(start, start + end),
+            Err(ParseError::NeedMoreData(needed, _)) => {
+                return Scan::NeedMoreData(start, buffer.len() - start + needed)
+            }
+            Err(_) => idx = start + 1,
+        }
+    }
+
+    Scan::NeedMoreData(buffer.len(), MIN_FRAME_LEN)
+}
+
+/// Ring buffer of one CAN ID
+///
+/// The data starts at `head` and wraps around the end of `buf`.
+struct Stream<const CAPACITY: usize> {
+    id: Option<u32>,
+    buf: [u8; CAPACITY],
+    head: usize,
+    len: usize,
+    /// Bytes of the last returned frame, dropped on the next call
+    pending: usize,
+    /// Do not scan again until the buffer holds this many bytes
+    needed: usize,
+}
+
+impl<const CAPACITY: usize> Stream<CAPACITY> {
+    const EMPTY: Self = Stream {
+        id: None,
+        buf: [0u8; CAPACITY],
+        head: 0,
+        len: 0,
+        pending: 0,
+        needed: 0,
+    };
+
+    /// Physical index of the byte at logical offset `offset`
+    fn index(&self, offset: usize) -> usize {
+        let index = self.head + offset;
+        if index >= CAPACITY {
+            index - CAPACITY
+        } else {
+            index
+        }
+    }
+
+    /// The buffered data, in order
+    fn as_split(&self) -> SplitBuf<'_> {
+        if self.head + self.len <= CAPACITY {
+            SplitBuf::new(&self.buf[self.head..self.head + self.len], &[])
+        } else {
+            SplitBuf::new(
+                &self.buf[self.head..],
+                &self.buf[..self.head + self.len - CAPACITY],
+            )
+        }
+    }
+
+    fn extend(&mut self, data: &[u8]) {
+        let tail = self.index(self.len);
+        let first = data.len().min(CAPACITY - tail);
+        self.buf[tail..tail + first].copy_from_slice(&data[..first]);
+        self.buf[..data.len() - first].copy_from_slice(&data[first..]);
+        self.len += data.len();
+    }
+
+    fn drop_front(&mut self, count: usize) {
+        self.head = self.index(count);
+        self.len -= count;
+        if self.len == 0 {
+            self.head = 0;
+        }
+    }
+
+    /// Move the data to the start of the buffer
+    fn make_contiguous(&mut self) {
+        self.buf.rotate_left(self.head);
+        self.head = 0;
+    }
+
+    fn reset(&mut self) {
+        self.head = 0;
+        self.len = 0;
+        self.pending = 0;
+        self.needed = 0;
+    }
+}
+
+/// Per-CAN-ID stream reassembler
+///
+/// The RM-S1 CAN bus carries one byte stream per CAN ID. This struct buffers the data of up to
+/// `STREAMS` CAN IDs in fixed-size buffers of `CAPACITY` bytes each, and yields complete frames
+/// once their CRCs have been checked. Invalid bytes are skipped.
+///
+/// `CAPACITY` should be at least [`crate::wire::MAX_FRAME_LEN`] + [`CAN_MAX_DLEN`] to hold any frame.
+///
+/// Each buffer is a ring, consumed bytes are dropped by moving its head. Frames are scanned across
+/// the wrap point without copying, the buffer is only rotated when a complete frame straddles the
+/// end of the ring, so that [`Self::poll`] can return it as one slice.
+///
+/// # Example
+///
+/// ```
+/// use robomaster_s1_proto::proto::StreamReassembler;
+///
+/// let mut reassembler = StreamReassembler::<4, 1031>::new();
+///
+/// reassembler
+///     .push(0x201, &[0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06])
+///     .unwrap();
+/// assert!(reassembler.poll(0x201).is_none());
+///
+/// reassembler
+///     .push(0x201, &[0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8])
+///     .unwrap();
+/// let frame = reassembler.poll(0x201).unwrap();
+/// assert_eq!(frame.cmd_set(), 0x48);
+/// ```
+pub struct StreamReassembler<const STREAMS: usize, const CAPACITY: usize> {
+    streams: [Stream<CAPACITY>; STREAMS],
+}
+
+impl<const STREAMS: usize, const CAPACITY: usize> Default for StreamReassembler<STREAMS, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const STREAMS: usize, const CAPACITY: usize> StreamReassembler<STREAMS, CAPACITY> {
+    pub const fn new() -> Self {
+        StreamReassembler {
+            streams: [Stream::EMPTY; STREAMS],
+        }
+    }
+
+    fn stream_index(&self, id: u32) -> Option<usize> {
+        self.streams.iter().position(|s| s.id == Some(id))
+    }
+
+    /// Append up to 8 data bytes received on CAN ID `id`
+    pub fn push(&mut self, id: u32, data: &[u8]) -> Result<(), ReassemblyError> {
+        if data.len() > CAN_MAX_DLEN {
+            return Err(ReassemblyError::DataTooLong(data.len()));
+        }
+
+        let index = match self.stream_index(id) {
+            Some(index) => index,
+            None => {
+                let index = self
+                    .streams
+                    .iter()
+                    .position(|s| s.id.is_none())
+                    .ok_or(ReassemblyError::NoFreeStream)?;
+                self.streams[index].id = Some(id);
+                index
+            }
+        };
+        let stream = &mut self.streams[index];
+
+        if stream.pending > 0 {
+            stream.drop_front(stream.pending);
+            stream.pending = 0;
+        }
+
+        if stream.len + data.len() > CAPACITY {
+            stream.reset();
+            return Err(ReassemblyError::BufferOverflow);
+        }
+
+        stream.extend(data);
+
+        Ok(())
+    }
+
+    /// Return the next complete frame received on CAN ID `id`, if any
+    ///
+    /// The frame stays in the buffer until the next call to [`Self::push`] or [`Self::poll`] for this ID.
+    pub fn poll(&mut self, id: u32) -> Option<RMWireFrameView<&[u8]>> {
+        let stream = &mut self.streams[self.stream_index(id)?];
+
+        if stream.pending > 0 {
+            stream.drop_front(stream.pending);
+            stream.pending = 0;
+        }
+
+        if stream.len == 0 || stream.len < stream.needed {
+            return None;
+        }
+        stream.needed = 0;
+
+        let (start, end) = match scan(stream.as_split()) {
+            Scan::Frame(start, end) => (start, end),
+            Scan::NeedMoreData(consumed, needed) => {
+                stream.drop_front(consumed);
+                stream.needed = needed;
+                return None;
+            }
+        };
+
+        if stream.head + end > CAPACITY && stream.head + start < CAPACITY {
+            stream.make_contiguous();
+        }
+
+        stream.pending = end;
+        let index = stream.index(start);
+        Some(RMWireFrameView::new(
+            &stream.buf[index..index + end - start],
+        ))
+    }
+
+    /// Discard the buffered data of CAN ID `id` and release its slot
+    pub fn remove(&mut self, id: u32) {
+        if let Some(index) = self.stream_index(id) {
+            self.streams[index].reset();
+            self.streams[index].id = None;
+        }
+    }
+}
+
+/// Heap-backed per-CAN-ID stream reassembler
+///
+/// Same as [`StreamReassembler`], but with an unbounded number of streams and growable buffers.
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+pub struct AllocStreamReassembler {
+    streams: alloc::collections::BTreeMap<u32, AllocStream>,
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+struct AllocStream {
+    buf: alloc::vec::Vec<u8>,
+    pending: usize,
+    needed: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl AllocStreamReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append data bytes received on CAN ID `id`
+    pub fn push(&mut self, id: u32, data: &[u8]) {
+        let stream = self.streams.entry(id).or_default();
+
+        stream.buf.drain(..stream.pending);
+        stream.pending = 0;
+        stream.buf.extend_from_slice(data);
+    }
+
+    /// Return the next complete frame received on CAN ID `id`, if any
+    ///
+    /// The frame stays in the buffer until the next call to [`Self::push`] or [`Self::poll`] for this ID.
+    pub fn poll(&mut self, id: u32) -> Option<RMWireFrameView<&[u8]>> {
+        let stream = self.streams.get_mut(&id)?;
+
+        stream.buf.drain(..stream.pending);
+        stream.pending = 0;
+
+        if stream.buf.is_empty() || stream.buf.len() < stream.needed {
+            return None;
+        }
+        stream.needed = 0;
+
+        let (start, end) = match scan(SplitBuf::new(&stream.buf, &[])) {
+            Scan::Frame(start, end) => (start, end),
+            Scan::NeedMoreData(consumed, needed) => {
+                stream.buf.drain(..consumed);
+                stream.needed = needed;
+                return None;
+            }
+        };
+
+        stream.pending = end;
+        Some(RMWireFrameView::new(&stream.buf[start..end]))
+    }
+
+    /// Discard the buffered data of CAN ID `id`
+    pub fn remove(&mut self, id: u32) {
+        self.streams.remove(&id);
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +775,24 @@ mod tests {
         assert_eq!(result, Err(ParseError::InvalidHeaderCRC(4)));
     }
 
+    #[test]
+    fn test_unknown_version() {
+        // Same as the frame in `test_full_parse`, with version 2 and recomputed CRCs
+        let mut input = [
+            0x00, 0x55, 0x0E, 0x08, 0x00, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0x00,
+            0x00,
+        ];
+        input[4] = rm_s1_crc8(&input[1..4]);
+        let crc16 = rm_s1_crc16(&input[1..13]).to_le_bytes();
+        input[13..].copy_from_slice(&crc16);
+
+        let result = parse_frame(&input);
+        assert_eq!(result, Err(ParseError::UnknownVersion(2, 4)));
+
+        let result = parse_frame_with(&input, VersionCheck::Ignore);
+        assert_eq!(result, Ok((&input[1..], input.len())));
+    }
+
     #[test]
     fn test_need_more_data() {
         let buffer = [0x00, 0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06];
@@ -141,4 +827,299 @@ mod tests {
         let result = parse_frame(&buffer);
         assert_eq!(result, Ok((&buffer[1..0xF], 0xF)));
     }
+
+    const FRAME: [u8; 14] = [
+        0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+    ];
+
+    #[test]
+    fn test_reassembler_interleaved_streams() {
+        let mut reassembler = StreamReassembler::<2, 64>::new();
+
+        reassembler.push(0x201, &FRAME[..8]).unwrap();
+        reassembler.push(0x202, &[0x00, 0x00]).unwrap();
+        reassembler.push(0x202, &FRAME[..8]).unwrap();
+        assert!(reassembler.poll(0x201).is_none());
+        assert!(reassembler.poll(0x202).is_none());
+
+        reassembler.push(0x201, &FRAME[8..]).unwrap();
+        let frame = reassembler.poll(0x201).unwrap();
+        assert_eq!(frame.as_bytes(), &FRAME);
+        assert!(reassembler.poll(0x201).is_none());
+
+        reassembler.push(0x202, &FRAME[8..]).unwrap();
+        let frame = reassembler.poll(0x202).unwrap();
+        assert_eq!(frame.as_bytes(), &FRAME);
+
+        assert_eq!(
+            reassembler.push(0x203, &[0x55]),
+            Err(ReassemblyError::NoFreeStream)
+        );
+        reassembler.remove(0x201);
+        assert!(reassembler.push(0x203, &[0x55]).is_ok());
+    }
+
+    #[test]
+    fn test_reassembler_back_to_back_frames() {
+        let mut reassembler = StreamReassembler::<1, 64>::new();
+
+        let mut input = [0u8; 32];
+        input[..14].copy_from_slice(&FRAME);
+        input[14] = 0x12; // garbage
+        input[15..29].copy_from_slice(&FRAME);
+
+        for chunk in input[..29].chunks(8) {
+            reassembler.push(0x211, chunk).unwrap();
+        }
+
+        assert_eq!(reassembler.poll(0x211).unwrap().as_bytes(), &FRAME);
+        assert_eq!(reassembler.poll(0x211).unwrap().as_bytes(), &FRAME);
+        assert!(reassembler.poll(0x211).is_none());
+    }
+
+    #[test]
+    fn test_reassembler_errors() {
+        let mut reassembler = StreamReassembler::<1, 16>::new();
+
+        assert_eq!(
+            reassembler.push(0x201, &[0u8; 9]),
+            Err(ReassemblyError::DataTooLong(9))
+        );
+
+        reassembler.push(0x201, &[0x55; 8]).unwrap();
+        reassembler.push(0x201, &[0x55; 8]).unwrap();
+        assert_eq!(
+            reassembler.push(0x201, &[0x55]),
+            Err(ReassemblyError::BufferOverflow)
+        );
+
+        reassembler.push(0x201, &FRAME[..8]).unwrap();
+        reassembler.push(0x201, &FRAME[8..]).unwrap();
+        assert_eq!(reassembler.poll(0x201).unwrap().as_bytes(), &FRAME);
+    }
+
+    #[test]
+    fn test_reassembler_wraps_around() {
+        // Frames and garbage bytes interleaved so that frames start at every offset of the ring
+        let mut reassembler = StreamReassembler::<1, 24>::new();
+
+        for i in 0..24 {
+            reassembler.push(0x202, &[0x12]).unwrap();
+            for chunk in FRAME.chunks(8) {
+                reassembler.push(0x202, chunk).unwrap();
+            }
+
+            let frame = reassembler.poll(0x202).unwrap();
+            assert_eq!(frame.as_bytes(), &FRAME, "iteration {}", i);
+            assert!(reassembler.poll(0x202).is_none());
+        }
+
+        // A frame split across the end of the ring with the next one already buffered
+        reassembler.push(0x202, &FRAME[..8]).unwrap();
+        reassembler.push(0x202, &FRAME[8..]).unwrap();
+        reassembler.push(0x202, &FRAME[..8]).unwrap();
+        assert_eq!(reassembler.poll(0x202).unwrap().as_bytes(), &FRAME);
+        reassembler.push(0x202, &FRAME[8..]).unwrap();
+        assert_eq!(reassembler.poll(0x202).unwrap().as_bytes(), &FRAME);
+        assert!(reassembler.poll(0x202).is_none());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_alloc_reassembler() {
+        let mut reassembler = AllocStreamReassembler::new();
+
+        reassembler.push(0x201, &[0x00]);
+        reassembler.push(0x201, &FRAME[..8]);
+        assert!(reassembler.poll(0x201).is_none());
+        reassembler.push(0x201, &FRAME[8..]);
+        reassembler.push(0x201, &FRAME[..3]);
+        assert_eq!(reassembler.poll(0x201).unwrap().as_bytes(), &FRAME);
+        assert!(reassembler.poll(0x201).is_none());
+        assert!(reassembler.poll(0x202).is_none());
+    }
+
+    #[test]
+    fn test_can_id_table() {
+        assert_eq!(can_id_for_pack_id(0x09), Some(0x201));
+        assert_eq!(can_id_for_pack_id(0xC3), Some(0x202));
+        assert_eq!(can_id_for_pack_id(0x04), Some(0x203));
+        assert_eq!(can_id_for_pack_id(0x17), Some(0x204));
+        assert_eq!(can_id_for_pack_id(0x38), Some(0x211));
+        assert_eq!(can_id_for_pack_id(0xD8), Some(0x216));
+        assert_eq!(can_id_for_pack_id(0x01), None);
+    }
+
+    #[test]
+    fn test_segment_speed_set() {
+        let buf = [
+            0x55, 0x19, 0x04, 0xe4, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x3F, 0x21, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x00, 0x00, 0x80, 0x3f, //
+            0x7d, 0x16,
+        ];
+        let frame = RMWireFrameView::new(&buf);
+
+        let segments = segment_frame(&frame).unwrap();
+        assert_eq!(segments.len(), 4);
+
+        let mut reassembler = StreamReassembler::<1, 64>::new();
+        for (i, (can_id, data)) in segments.enumerate() {
+            assert_eq!(can_id, 0x201);
+            assert_eq!(data.len(), if i < 3 { 8 } else { 1 });
+            reassembler.push(can_id, data).unwrap();
+        }
+        assert_eq!(reassembler.poll(0x201).unwrap().as_bytes(), &buf);
+    }
+
+    #[test]
+    fn test_frames_iterator() {
+        let mut input = [0u8; 64];
+        // Good frame
+        input[..14].copy_from_slice(&FRAME);
+        // Frame with a corrupted payload
+        input[14..28].copy_from_slice(&FRAME);
+        input[25] = 0x02;
+        // Garbage, then a good frame
+        input[28] = 0x12;
+        input[29..43].copy_from_slice(&FRAME);
+        // Truncated frame
+        input[43..51].copy_from_slice(&FRAME[..8]);
+
+        let mut iter = frames(&input[..51]);
+        assert_eq!(iter.next().unwrap().unwrap().as_bytes(), &FRAME);
+        assert_eq!(iter.next().unwrap(), Err(ParseError::InvalidPacketCRC(3)));
+        assert_eq!(iter.next().unwrap().unwrap().as_bytes(), &FRAME);
+        assert_eq!(iter.next().unwrap(), Err(ParseError::NeedMoreData(6, 0)));
+        assert_eq!(iter.offset(), 43);
+        assert_eq!(iter.remaining(), &FRAME[..8]);
+        assert!(iter.next().is_none());
+
+        assert_eq!(frames(&input[..43]).filter_map(Result::ok).count(), 2);
+        assert_eq!(frames(&[0x00, 0x01]).count(), 0);
+    }
+
+    #[test]
+    fn test_parse_frame_split() {
+        // A frame that wrapped around the end of a ring buffer, with some garbage before it
+        let mut ring = [0u8; 16];
+        ring[10] = 0x12;
+        ring[11..].copy_from_slice(&FRAME[..5]);
+        ring[..9].copy_from_slice(&FRAME[5..]);
+
+        let (frame, consumed) = parse_frame_split(&ring[10..], &ring[..9]).unwrap();
+        assert_eq!(consumed, 15);
+        assert_eq!(frame.as_slices(), (&FRAME[..5], &FRAME[5..]));
+        assert!(crate::wire::RMSplitFrameView::new(frame).is_valid());
+
+        let result = parse_frame_split(&ring[10..], &ring[..4]);
+        assert_eq!(result, Err(ParseError::NeedMoreData(5, 1)));
+
+        ring[2] = 0x02;
+        let result = parse_frame_split(&ring[10..], &ring[..9]);
+        assert_eq!(result, Err(ParseError::InvalidPacketCRC(4)));
+    }
+
+    /// Collect all the frames found by repeatedly calling `resync`
+    fn resync_all(mut buffer: &[u8]) -> std::vec::Vec<&[u8]> {
+        let mut frames = std::vec::Vec::new();
+        while let Resync::Frame { frame, dropped } = resync(buffer) {
+            frames.push(frame);
+            buffer = &buffer[dropped + frame.len()..];
+        }
+        frames
+    }
+
+    #[test]
+    fn test_resync_stray_sof() {
+        let mut input = [0u8; 15];
+        input[0] = SOF;
+        input[1..].copy_from_slice(&FRAME);
+
+        // The fixed skip of `parse_frame` jumps over the real SOF
+        assert_eq!(parse_frame(&input), Err(ParseError::InvalidHeaderCRC(3)));
+        assert_eq!(parse_frame(&input[3..]), Err(ParseError::NoStartOfFrame));
+
+        assert_eq!(
+            resync(&input),
+            Resync::Frame {
+                frame: &FRAME,
+                dropped: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_resync_keeps_tail() {
+        let input = [0x00, 0x12, 0x55, 0x0E];
+        assert_eq!(
+            resync(&input),
+            Resync::Incomplete {
+                dropped: 2,
+                needed: 2
+            }
+        );
+
+        assert_eq!(
+            resync(&FRAME[..10]),
+            Resync::Incomplete {
+                dropped: 0,
+                needed: 4
+            }
+        );
+
+        assert_eq!(
+            resync(&[0x00, 0x12]),
+            Resync::Incomplete {
+                dropped: 2,
+                needed: MIN_FRAME_LEN
+            }
+        );
+    }
+
+    #[test]
+    fn test_resync_implausible_length() {
+        // Valid header CRC, but a length of 2
+        let mut input = [0u8; 18];
+        input[..3].copy_from_slice(&[0x55, 0x02, 0x04]);
+        input[3] = rm_s1_crc8(&input[..3]);
+        input[4..].copy_from_slice(&FRAME);
+
+        assert_eq!(parse_frame(&input), Err(ParseError::InvalidLength(1)));
+        assert_eq!(resync_all(&input), [&FRAME[..]]);
+    }
+
+    #[test]
+    fn test_resync_corrupted_capture() {
+        // VBUS push capture from `topic_view.rs`, followed by another frame
+        let capture = [
+            0x55, 0x31, 0x04, 0x53, 0x03, 0x04, 0x8d, 0x04, //
+            0x20, 0x48, 0x08, 0x00, 0x00, 0x99, 0x1d, 0x00, //
+            0x00, 0xf0, 0xa0, 0x73, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0xac, 0x2e, 0xdd, //
+            0x00, 0x17, 0xfd, 0xff, 0xff, 0x54, 0x00, 0x3f, //
+            0x41,
+        ];
+        let mut input = [0u8; 63];
+        input[..49].copy_from_slice(&capture);
+        input[49..].copy_from_slice(&FRAME);
+
+        assert_eq!(resync_all(&input), [&capture[..], &FRAME[..]]);
+
+        // Corrupt every byte of the first frame in turn, the second frame must always be found
+        for i in 0..capture.len() {
+            for corruption in [0x01, 0x55 ^ capture[i]] {
+                let mut corrupted = input;
+                corrupted[i] ^= corruption;
+                if corrupted[i] == capture[i] {
+                    continue;
+                }
+
+                let frames = resync_all(&corrupted);
+                assert_eq!(frames.last(), Some(&&FRAME[..]), "corrupted byte {}", i);
+            }
+        }
+    }
 }
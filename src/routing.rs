@@ -0,0 +1,242 @@
+//! Default receivers of commands
+//!
+//! Every command is handled by one node on the bus, e.g. the chassis (0xC3) for
+//! [`RMCommandType::SPEED_SET`] or the gun (0x17) for [`RMCommandType::SHOOT_CMD`].
+//! [`default_receiver`] maps a (CMD_SET, CMD_ID) pair to that node.
+//!
+//! Commands of nodes with several instances default to index 0 of their host type, e.g.
+//! [`RMS1Address::armor_id`] for the armor commands. Which nodes listen on that address is not
+//! known. A [`Route`] overrides the default and is the way to address one of
+//! [`RMS1Address::armor1_id`] to [`RMS1Address::armor6_id`]:
+//!
+//! ```
+//! use robomaster_s1_proto::{address::RMS1Address, duss::cmd_set_rm::RMCommandType};
+//! use robomaster_s1_proto::routing::{resolve, Route};
+//!
+//! const ROUTES: [Route; 1] = [Route::command(
+//!     0x3F,
+//!     RMCommandType::ARMOR_LED_SET as u8,
+//!     RMS1Address::armor3_id,
+//! )];
+//!
+//! assert_eq!(
+//!     resolve(&ROUTES, 0x3F, RMCommandType::ARMOR_LED_SET as u8),
+//!     Some(RMS1Address::armor3_id)
+//! );
+//! ```
+
+use crate::{
+    address::RMS1Address,
+    duss::{
+        cmd_set_gimbal::GimbalCommandType, cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType,
+    },
+};
+
+const CMDSET_GIMBAL: u8 = CommandSetType::GIMBAL as u8;
+const CMDSET_RM: u8 = CommandSetType::RM as u8;
+const CMDSET_VBUS: u8 = CommandSetType::VIRTUAL_BUS as u8;
+
+const CMDID_GIMBAL_SET_MODE: u8 = GimbalCommandType::GIMBAL_SET_MODE as u8;
+
+/// Commands of the RM set handled by the chassis
+const CHASSIS_COMMANDS: [RMCommandType; 19] = [
+    RMCommandType::WORK_MODE_SET,
+    RMCommandType::WHEEL_SPEED_SET,
+    RMCommandType::SPEED_SET,
+    RMCommandType::FOLLOW_MODE_SET,
+    RMCommandType::FPV_MODE_SPEED_SET,
+    RMCommandType::GROUND_MODE_SET,
+    RMCommandType::POSITION_SET,
+    RMCommandType::WHEEL_STATUS_SET,
+    RMCommandType::WHEEL_STATUS_GET,
+    RMCommandType::SPEED_MODE_SET,
+    RMCommandType::SET_CHASSIS_PWM_FREQ,
+    RMCommandType::GET_CHASSIS_PWM_FREQ,
+    RMCommandType::SET_CHASSIS_PWM_VALUE,
+    RMCommandType::GET_CHASSIS_PWM_VALUE,
+    RMCommandType::SET_CHASSIS_SPEED,
+    RMCommandType::SET_TANK_WORK_MODE,
+    RMCommandType::GET_TANK_WORK_MODE,
+    RMCommandType::FC_RMC,
+    RMCommandType::FC_GET_STATE,
+];
+
+const GUN_COMMANDS: [RMCommandType; 6] = [
+    RMCommandType::WATER_GUN_PARM_SET,
+    RMCommandType::SHOOT_CMD,
+    RMCommandType::SHOOT_GET_STATE,
+    RMCommandType::SHOOT_MODE_SET,
+    RMCommandType::SHOOT_MODE_GET,
+    RMCommandType::GUN_LED_SET,
+];
+
+const ARMOR_COMMANDS: [RMCommandType; 3] = [
+    RMCommandType::ARMOR_VOICE_PARAMS_SET,
+    RMCommandType::ARMOR_GET_STATE,
+    RMCommandType::ARMOR_LED_SET,
+];
+
+const GIMBAL_COMMANDS: [RMCommandType; 2] = [
+    RMCommandType::GIMBAL_DEGREE_SET,
+    RMCommandType::GIMBAL_RESET_POSITION_SET,
+];
+
+const fn contains(commands: &[RMCommandType], cmd_id: u8) -> bool {
+    let mut i = 0;
+    while i < commands.len() {
+        if commands[i] as u8 == cmd_id {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Node that handles the command, if known
+pub const fn default_receiver(cmd_set: u8, cmd_id: u8) -> Option<RMS1Address> {
+    match cmd_set {
+        CMDSET_RM => {
+            if contains(&CHASSIS_COMMANDS, cmd_id) {
+                Some(RMS1Address::chassis_id)
+            } else if contains(&GUN_COMMANDS, cmd_id) {
+                Some(RMS1Address::gun_id)
+            } else if contains(&ARMOR_COMMANDS, cmd_id) {
+                Some(RMS1Address::armor_id)
+            } else if contains(&GIMBAL_COMMANDS, cmd_id) {
+                Some(RMS1Address::gimbal_id)
+            } else {
+                None
+            }
+        }
+        // The gimbal work mode decides how the chassis follows the gimbal
+        CMDSET_GIMBAL if cmd_id == CMDID_GIMBAL_SET_MODE => Some(RMS1Address::chassis_id),
+        CMDSET_GIMBAL => Some(RMS1Address::gimbal_id),
+        CMDSET_VBUS => Some(RMS1Address::fc_id),
+        _ => None,
+    }
+}
+
+/// Receiver overriding [`default_receiver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Route {
+    pub cmd_set: u8,
+    /// `None` matches all the commands of the set
+    pub cmd_id: Option<u8>,
+    pub receiver: RMS1Address,
+}
+
+impl Route {
+    /// Route a single command
+    pub const fn command(cmd_set: u8, cmd_id: u8, receiver: RMS1Address) -> Self {
+        Route {
+            cmd_set,
+            cmd_id: Some(cmd_id),
+            receiver,
+        }
+    }
+
+    /// Route all the commands of a set
+    pub const fn cmd_set(cmd_set: u8, receiver: RMS1Address) -> Self {
+        Route {
+            cmd_set,
+            cmd_id: None,
+            receiver,
+        }
+    }
+
+    pub const fn matches(&self, cmd_set: u8, cmd_id: u8) -> bool {
+        self.cmd_set == cmd_set
+            && match self.cmd_id {
+                Some(id) => id == cmd_id,
+                None => true,
+            }
+    }
+}
+
+/// Receiver of the command
+///
+/// The first matching route wins, [`default_receiver`] is used if none matches.
+pub const fn resolve(routes: &[Route], cmd_set: u8, cmd_id: u8) -> Option<RMS1Address> {
+    let mut i = 0;
+    while i < routes.len() {
+        if routes[i].matches(cmd_set, cmd_id) {
+            return Some(routes[i].receiver);
+        }
+        i += 1;
+    }
+    default_receiver(cmd_set, cmd_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_receiver() {
+        let receiver = |cmd_set: CommandSetType, cmd_id: u8| {
+            default_receiver(cmd_set as u8, cmd_id).map(RMS1Address::pack_id)
+        };
+
+        assert_eq!(
+            receiver(CommandSetType::RM, RMCommandType::SPEED_SET as u8),
+            Some(0xC3)
+        );
+        assert_eq!(
+            receiver(CommandSetType::RM, RMCommandType::SHOOT_CMD as u8),
+            Some(0x17)
+        );
+        assert_eq!(
+            receiver(CommandSetType::RM, RMCommandType::ARMOR_LED_SET as u8),
+            Some(0x18)
+        );
+        assert_eq!(
+            receiver(
+                CommandSetType::GIMBAL,
+                GimbalCommandType::GIMBAL_SET_VEL as u8
+            ),
+            Some(0x04)
+        );
+        assert_eq!(
+            receiver(
+                CommandSetType::GIMBAL,
+                GimbalCommandType::GIMBAL_SET_MODE as u8
+            ),
+            Some(0xC3)
+        );
+        assert_eq!(receiver(CommandSetType::VIRTUAL_BUS, 0x03), Some(0x03));
+        assert_eq!(
+            receiver(CommandSetType::RM, RMCommandType::PLAY_SOUND as u8),
+            None
+        );
+        assert_eq!(receiver(CommandSetType::CAMERA, 0x01), None);
+    }
+
+    #[test]
+    fn test_routes() {
+        const ROUTES: [Route; 2] = [
+            Route::command(
+                CMDSET_RM,
+                RMCommandType::ARMOR_LED_SET as u8,
+                RMS1Address::armor3_id,
+            ),
+            Route::cmd_set(CMDSET_GIMBAL, RMS1Address::gimbal_id),
+        ];
+
+        assert_eq!(
+            resolve(&ROUTES, CMDSET_RM, RMCommandType::ARMOR_LED_SET as u8),
+            Some(RMS1Address::armor3_id)
+        );
+        assert_eq!(
+            resolve(&ROUTES, CMDSET_RM, RMCommandType::ARMOR_GET_STATE as u8),
+            Some(RMS1Address::armor_id)
+        );
+        assert_eq!(
+            resolve(&ROUTES, CMDSET_GIMBAL, CMDID_GIMBAL_SET_MODE),
+            Some(RMS1Address::gimbal_id)
+        );
+        assert_eq!(
+            resolve(&[], CMDSET_GIMBAL, CMDID_GIMBAL_SET_MODE),
+            Some(RMS1Address::chassis_id)
+        );
+    }
+}
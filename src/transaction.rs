@@ -0,0 +1,416 @@
+//! Request/response correlation
+//!
+//! Requests that set `NEED_ACK` or `NEED_ACK_AFTER_EXEC` are answered by a frame with `IS_ACK`
+//! set, the same sequence number, CMD_SET and CMD_ID, and the sender and receiver swapped.
+//!
+//! [`TransactionTable`] allocates sequence numbers, keeps track of the outstanding requests and
+//! matches the ACKs. It does not perform any IO: the caller passes the current time in milliseconds
+//! (from any monotonic clock, wrapping at `u32::MAX`) and resends the requests reported by [`TransactionTable::poll`].
+
+use crate::wire::RMWireFrameView;
+
+/// Timeout and retransmission settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetransmitConfig {
+    /// Time to wait for an ACK before retransmitting, in milliseconds
+    pub timeout_ms: u32,
+    /// Number of retransmissions before giving up
+    pub max_retries: u8,
+}
+
+impl Default for RetransmitConfig {
+    fn default() -> Self {
+        RetransmitConfig {
+            timeout_ms: 100,
+            max_retries: 3,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TransactionError {
+    /// All slots for outstanding requests are taken
+    TableFull,
+    /// All slots for (sender, receiver) sequence counters are taken
+    TooManyPeers,
+}
+
+/// An outstanding request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transaction {
+    pub sender_id: u8,
+    pub receiver_id: u8,
+    pub sequence_number: u16,
+    pub cmd_set: u8,
+    pub cmd_id: u8,
+    /// Number of retransmissions so far
+    pub retries: u8,
+    sent_at: u32,
+}
+
+/// Events reported by [`TransactionTable::poll`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionEvent {
+    /// The request was not acknowledged in time and must be sent again, with the same sequence number
+    Retransmit(Transaction),
+    /// The request was not acknowledged after all retransmissions and was removed
+    TimedOut(Transaction),
+}
+
+/// Table of outstanding requests
+///
+/// Holds up to `PENDING` outstanding requests and sequence counters for up to `PEERS` (sender, receiver) pairs.
+///
+/// # Example
+///
+/// ```
+/// use robomaster_s1_proto::{
+///     transaction::{RetransmitConfig, TransactionTable},
+///     wire::RMWireFrameBuilder,
+/// };
+///
+/// let mut table = TransactionTable::<8, 4>::new(RetransmitConfig::default());
+///
+/// let sequence_number = table.next_sequence(0x09, 0x04).unwrap();
+/// let request = RMWireFrameBuilder::new()
+///     .sender_id(0x09)
+///     .receiver_id(0x04)
+///     .sequence_number(sequence_number)
+///     .need_ack(true)
+///     .cmd_set(0x04)
+///     .cmd_id(0x4C)
+///     .payload(&[0x00, 0x00])
+///     .build::<32>()
+///     .unwrap();
+/// table.register(&request, 0).unwrap();
+///
+/// let ack = RMWireFrameBuilder::new()
+///     .sender_id(0x04)
+///     .receiver_id(0x09)
+///     .sequence_number(sequence_number)
+///     .is_ack(true)
+///     .cmd_set(0x04)
+///     .cmd_id(0x4C)
+///     .payload(&[0x00])
+///     .build::<32>()
+///     .unwrap();
+/// assert!(table.handle_ack(&ack).is_some());
+/// assert!(table.is_empty());
+/// ```
+pub struct TransactionTable<const PENDING: usize, const PEERS: usize> {
+    config: RetransmitConfig,
+    pending: [Option<Transaction>; PENDING],
+    /// (sender, receiver, next sequence number)
+    sequences: [Option<(u8, u8, u16)>; PEERS],
+}
+
+impl<const PENDING: usize, const PEERS: usize> TransactionTable<PENDING, PEERS> {
+    pub const fn new(config: RetransmitConfig) -> Self {
+        TransactionTable {
+            config,
+            pending: [None; PENDING],
+            sequences: [None; PEERS],
+        }
+    }
+
+    pub fn config(&self) -> &RetransmitConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: RetransmitConfig) {
+        self.config = config;
+    }
+
+    /// Allocate the next sequence number for frames from `sender_id` to `receiver_id`
+    pub fn next_sequence(
+        &mut self,
+        sender_id: u8,
+        receiver_id: u8,
+    ) -> Result<u16, TransactionError> {
+        let slot = match self
+            .sequences
+            .iter()
+            .position(|s| matches!(s, Some((s, r, _)) if *s == sender_id && *r == receiver_id))
+        {
+            Some(slot) => slot,
+            None => {
+                let slot = self
+                    .sequences
+                    .iter()
+                    .position(|s| s.is_none())
+                    .ok_or(TransactionError::TooManyPeers)?;
+                self.sequences[slot] = Some((sender_id, receiver_id, 0));
+                slot
+            }
+        };
+
+        let (_, _, next) = self.sequences[slot].as_mut().unwrap();
+        let sequence_number = *next;
+        *next = next.wrapping_add(1);
+
+        Ok(sequence_number)
+    }
+
+    /// Record a request sent at `now`
+    ///
+    /// Frames that set neither `NEED_ACK` nor `NEED_ACK_AFTER_EXEC` are ignored. Registering a
+    /// request that is already outstanding (same sender, receiver and sequence number) restarts
+    /// its timeout.
+    pub fn register<T: AsRef<[u8]>>(
+        &mut self,
+        frame: &RMWireFrameView<T>,
+        now: u32,
+    ) -> Result<(), TransactionError> {
+        if !(frame.need_ack() || frame.need_ack_after_exec()) || frame.is_ack() {
+            return Ok(());
+        }
+
+        let transaction = Transaction {
+            sender_id: frame.sender_id(),
+            receiver_id: frame.receiver_id(),
+            sequence_number: frame.sequence_number(),
+            cmd_set: frame.cmd_set(),
+            cmd_id: frame.cmd_id(),
+            retries: 0,
+            sent_at: now,
+        };
+
+        let existing = self.pending.iter().position(|t| {
+            matches!(t, Some(t) if t.sender_id == transaction.sender_id
+                && t.receiver_id == transaction.receiver_id
+                && t.sequence_number == transaction.sequence_number)
+        });
+        let slot = match existing {
+            Some(slot) => slot,
+            None => self
+                .pending
+                .iter()
+                .position(|t| t.is_none())
+                .ok_or(TransactionError::TableFull)?,
+        };
+        self.pending[slot] = Some(transaction);
+
+        Ok(())
+    }
+
+    /// Match an incoming ACK against the outstanding requests
+    ///
+    /// Returns the acknowledged request, which is removed from the table.
+    pub fn handle_ack<T: AsRef<[u8]>>(
+        &mut self,
+        frame: &RMWireFrameView<T>,
+    ) -> Option<Transaction> {
+        if !frame.is_ack() {
+            return None;
+        }
+
+        let slot = self.pending.iter().position(|t| {
+            matches!(t, Some(t) if t.sender_id == frame.receiver_id()
+                && t.receiver_id == frame.sender_id()
+                && t.sequence_number == frame.sequence_number()
+                && t.cmd_set == frame.cmd_set()
+                && t.cmd_id == frame.cmd_id())
+        })?;
+
+        self.pending[slot].take()
+    }
+
+    /// Check the outstanding requests for timeouts at `now`
+    ///
+    /// Returns one event per call, call until it returns `None`.
+    pub fn poll(&mut self, now: u32) -> Option<TransactionEvent> {
+        let timeout_ms = self.config.timeout_ms;
+        let max_retries = self.config.max_retries;
+
+        let slot = self
+            .pending
+            .iter()
+            .position(|t| matches!(t, Some(t) if now.wrapping_sub(t.sent_at) >= timeout_ms))?;
+        let transaction = self.pending[slot].as_mut().unwrap();
+
+        if transaction.retries < max_retries {
+            transaction.retries += 1;
+            transaction.sent_at = now;
+            Some(TransactionEvent::Retransmit(*transaction))
+        } else {
+            self.pending[slot].take().map(TransactionEvent::TimedOut)
+        }
+    }
+
+    /// Time at which the next outstanding request times out, as seen at `now`
+    ///
+    /// The deadlines are compared by the time left until them, so the result is correct
+    /// when the clock wraps. Requests that are already overdue come first.
+    pub fn next_deadline(&self, now: u32) -> Option<u32> {
+        let timeout_ms = self.config.timeout_ms;
+        self.pending
+            .iter()
+            .flatten()
+            .min_by_key(|t| timeout_ms.saturating_sub(now.wrapping_sub(t.sent_at)))
+            .map(|t| t.sent_at.wrapping_add(timeout_ms))
+    }
+
+    /// Number of outstanding requests
+    pub fn len(&self) -> usize {
+        self.pending.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all outstanding requests
+    pub fn clear(&mut self) {
+        self.pending = [None; PENDING];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        duss::{cmd_set_rm::RMCommandType, cmd_set_types::CommandSetType},
+        wire::{RMWireFrameBuf, RMWireFrameBuilder},
+    };
+
+    fn work_mode_set(sequence_number: u16, is_ack: bool) -> RMWireFrameView<RMWireFrameBuf<32>> {
+        let (sender_id, receiver_id) = if is_ack { (0xC3, 0x09) } else { (0x09, 0xC3) };
+        RMWireFrameBuilder::new()
+            .sender_id(sender_id)
+            .receiver_id(receiver_id)
+            .sequence_number(sequence_number)
+            .is_ack(is_ack)
+            .need_ack(!is_ack)
+            .cmd_set(CommandSetType::RM as u8)
+            .cmd_id(RMCommandType::WORK_MODE_SET as u8)
+            .payload(&[0x01])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sequence_numbers() {
+        let mut table = TransactionTable::<4, 2>::new(RetransmitConfig::default());
+
+        assert_eq!(table.next_sequence(0x09, 0xC3), Ok(0));
+        assert_eq!(table.next_sequence(0x09, 0xC3), Ok(1));
+        assert_eq!(table.next_sequence(0x09, 0x04), Ok(0));
+        assert_eq!(table.next_sequence(0x09, 0xC3), Ok(2));
+        assert_eq!(
+            table.next_sequence(0x09, 0x17),
+            Err(TransactionError::TooManyPeers)
+        );
+    }
+
+    #[test]
+    fn test_ack_matching() {
+        let mut table = TransactionTable::<4, 2>::new(RetransmitConfig::default());
+
+        table.register(&work_mode_set(7, false), 0).unwrap();
+        assert_eq!(table.len(), 1);
+
+        // Wrong sequence number
+        assert_eq!(table.handle_ack(&work_mode_set(8, true)), None);
+        // Not an ACK
+        assert_eq!(table.handle_ack(&work_mode_set(7, false)), None);
+
+        let transaction = table.handle_ack(&work_mode_set(7, true)).unwrap();
+        assert_eq!(transaction.sequence_number, 7);
+        assert_eq!(transaction.cmd_id, RMCommandType::WORK_MODE_SET as u8);
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_need_ack_after_exec() {
+        let mut table = TransactionTable::<4, 2>::new(RetransmitConfig::default());
+        let request = |need_ack_after_exec| {
+            RMWireFrameBuilder::new()
+                .sender_id(0x09)
+                .receiver_id(0xC3)
+                .sequence_number(1)
+                .need_ack_after_exec(need_ack_after_exec)
+                .cmd_set(CommandSetType::RM as u8)
+                .cmd_id(RMCommandType::WORK_MODE_SET as u8)
+                .payload(&[0x01])
+                .build::<32>()
+                .unwrap()
+        };
+
+        // No ACK requested
+        table.register(&request(false), 0).unwrap();
+        assert!(table.is_empty());
+
+        table.register(&request(true), 0).unwrap();
+        assert_eq!(table.len(), 1);
+        assert!(table.handle_ack(&work_mode_set(1, true)).is_some());
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_retransmit_and_timeout() {
+        let mut table = TransactionTable::<2, 1>::new(RetransmitConfig {
+            timeout_ms: 10,
+            max_retries: 1,
+        });
+
+        table
+            .register(&work_mode_set(1, false), u32::MAX - 5)
+            .unwrap();
+        table.register(&work_mode_set(2, false), 0).unwrap();
+        assert_eq!(
+            table.register(&work_mode_set(3, false), 0),
+            Err(TransactionError::TableFull)
+        );
+        assert_eq!(table.next_deadline(0), Some(4));
+
+        assert_eq!(table.poll(3), None);
+        match table.poll(4) {
+            Some(TransactionEvent::Retransmit(t)) => {
+                assert_eq!(t.sequence_number, 1);
+                assert_eq!(t.retries, 1);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert_eq!(table.poll(4), None);
+
+        assert!(matches!(
+            table.poll(10),
+            Some(TransactionEvent::Retransmit(t)) if t.sequence_number == 2
+        ));
+        assert!(matches!(
+            table.poll(14),
+            Some(TransactionEvent::TimedOut(t)) if t.sequence_number == 1
+        ));
+        assert_eq!(table.len(), 1);
+
+        table.clear();
+        assert_eq!(table.poll(100), None);
+    }
+
+    #[test]
+    fn test_next_deadline_wraps() {
+        let mut table = TransactionTable::<2, 1>::new(RetransmitConfig {
+            timeout_ms: 10,
+            max_retries: 1,
+        });
+        assert_eq!(table.next_deadline(0), None);
+
+        // The earlier deadline is the larger number
+        table
+            .register(&work_mode_set(1, false), u32::MAX - 20)
+            .unwrap();
+        table
+            .register(&work_mode_set(2, false), u32::MAX - 5)
+            .unwrap();
+        assert_eq!(table.next_deadline(u32::MAX - 5), Some(u32::MAX - 10));
+
+        // Overdue requests come first
+        assert_eq!(table.next_deadline(2), Some(u32::MAX - 10));
+
+        assert!(matches!(
+            table.poll(u32::MAX - 10),
+            Some(TransactionEvent::Retransmit(t)) if t.sequence_number == 1
+        ));
+        assert_eq!(table.next_deadline(u32::MAX - 10), Some(4));
+    }
+}
@@ -7,12 +7,13 @@
 //! - SOF (Start of Frame) byte: 0x55
 //! - low byte of the packet length
 //! - 0b00000100 (0x4) & high 2 bits of the packet length
+//!   - The upper 6 bits are the protocol version, which is always 1 on the RM-S1
 //! - Header CRC8
 //! - Sender ID (1 byte)
 //! - Receiver ID (1 byte)
 //! - Low byte of the packet sequence number
 //! - High byte of the packet sequence number
-//! - 0bX0X00000, bit 8 = IS_ACK, bit 6 = NEED_ACK
+//! - 0bXXX00YYY, bit 8 = IS_ACK, bit 7 = NEED_ACK_AFTER_EXEC, bit 6 = NEED_ACK, YYY = encrypt type
 //! - CMD_SET (1 byte)
 //! - CMD_ID (1 byte)
 //! - Payload (variable length)
@@ -20,7 +21,58 @@
 
 use num_enum::TryFromPrimitive;
 
-#[derive(Debug, PartialEq, Eq, TryFromPrimitive)]
+use crate::address::RMS1Address;
+use crate::cipher::{CipherError, FrameCipher};
+use crate::routing::{resolve, Route};
+
+/// Length of the frame header (SOF up to and including CMD_ID)
+pub const HEADER_LEN: usize = 11;
+
+/// Length of the payload CRC16 trailer
+pub const CRC16_LEN: usize = 2;
+
+/// Smallest possible frame (empty payload)
+pub const MIN_FRAME_LEN: usize = HEADER_LEN + CRC16_LEN;
+
+/// Largest possible frame, limited by the 10-bit length field
+pub const MAX_FRAME_LEN: usize = 0x3FF;
+
+/// Protocol version used by the RM-S1 (upper 6 bits of the third byte)
+pub const RM_S1_VERSION: u8 = 1;
+
+/// How frames with an unknown protocol version are handled
+///
+/// Other DUML devices (e.g. the RoboMaster EP or drones) use other versions,
+/// so checking the version helps telling them apart on a mixed bus.
+#[derive(Debug, Clone, Copy)]
+pub enum VersionCheck {
+    /// Reject the frame
+    Strict,
+    /// Accept the frame after calling the function with the unknown version
+    Warn(fn(u8)),
+    /// Accept the frame
+    Ignore,
+}
+
+impl VersionCheck {
+    /// Returns true if a frame with the given version should be accepted
+    pub fn accept(&self, version: u8) -> bool {
+        if version == RM_S1_VERSION {
+            return true;
+        }
+
+        match self {
+            VersionCheck::Strict => false,
+            VersionCheck::Warn(warn) => {
+                warn(version);
+                true
+            }
+            VersionCheck::Ignore => true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, TryFromPrimitive)]
 #[repr(u8)]
 #[allow(non_camel_case_types)]
 pub enum EncryptType {
@@ -34,6 +86,37 @@ pub enum EncryptType {
     AES_256_ENC = 0x07,
 }
 
+/// Errors returned by the checked constructors of [`RMWireFrameView`] and the command views
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FrameError {
+    /// The buffer is shorter than the smallest possible frame
+    ///
+    /// The usize is the length of the buffer
+    TooShort(usize),
+    /// The first byte is not the SOF byte (0x55)
+    NoStartOfFrame,
+    /// The length field does not match the buffer
+    ///
+    /// The first usize is the length field, the second usize is the length of the buffer
+    LengthMismatch(usize, usize),
+    InvalidHeaderCRC,
+    InvalidPacketCRC,
+    /// The protocol version is not [`RM_S1_VERSION`]
+    ///
+    /// Contains the version of the frame
+    UnknownVersion(u8),
+    /// The frame is not the command expected by the view
+    ///
+    /// Contains the (cmd_set, cmd_id) of the frame
+    WrongCommand(u8, u8),
+    /// The payload length is not valid for the command
+    ///
+    /// The usize is the length of the payload
+    InvalidPayloadLength(usize),
+    /// The view expects an ACK but the frame is a request
+    NotAck,
+}
+
 #[derive(PartialEq, Eq, Clone)]
 pub struct RMWireFrameView<T: AsRef<[u8]>> {
     buf: T,
@@ -44,7 +127,94 @@ impl<T: AsRef<[u8]>> RMWireFrameView<T> {
         RMWireFrameView { buf }
     }
 
+    /// Create a view after checking that the buffer holds exactly one valid frame
+    ///
+    /// All accessors are guaranteed not to panic on a view returned by this function.
+    pub fn new_checked(buf: T) -> Result<RMWireFrameView<T>, FrameError> {
+        let frame = RMWireFrameView { buf };
+        frame.check()?;
+        Ok(frame)
+    }
+
+    /// Check that the buffer holds exactly one valid frame
+    pub fn check(&self) -> Result<(), FrameError> {
+        self.check_with(VersionCheck::Strict)
+    }
+
+    /// Check that the buffer holds exactly one valid frame, with the given version handling
+    pub fn check_with(&self, version_check: VersionCheck) -> Result<(), FrameError> {
+        let buffer = self.buf.as_ref();
+
+        if buffer.len() < MIN_FRAME_LEN {
+            return Err(FrameError::TooShort(buffer.len()));
+        }
+        if buffer[0] != 0x55 {
+            return Err(FrameError::NoStartOfFrame);
+        }
+        if self.header_crc8() != self.crc8_computed() {
+            return Err(FrameError::InvalidHeaderCRC);
+        }
+        if !version_check.accept(self.version()) {
+            return Err(FrameError::UnknownVersion(self.version()));
+        }
+        let length = self.packet_length_field() as usize;
+        if length != buffer.len() {
+            return Err(FrameError::LengthMismatch(length, buffer.len()));
+        }
+        if self.packet_crc16_field() != self.crc16_computed() {
+            return Err(FrameError::InvalidPacketCRC);
+        }
+
+        Ok(())
+    }
+
+    /// Check that the frame carries the given command, the CRCs are not checked
+    pub(crate) fn check_command(&self, cmd_set: u8, cmd_id: u8) -> Result<(), FrameError> {
+        if self.cmd_set() != cmd_set || self.cmd_id() != cmd_id {
+            return Err(FrameError::WrongCommand(self.cmd_set(), self.cmd_id()));
+        }
+
+        Ok(())
+    }
+
+    /// The raw bytes of the frame
+    pub fn as_bytes(&self) -> &[u8] {
+        self.buf.as_ref()
+    }
+
+    /// Copy the payload to `out` and decrypt it according to [`Self::encrypt_type`]
+    ///
+    /// Returns the decrypted payload.
+    pub fn decrypt_payload_into<'b>(
+        &self,
+        cipher: &dyn FrameCipher,
+        out: &'b mut [u8],
+    ) -> Result<&'b [u8], CipherError> {
+        let payload = self.payload();
+        if out.len() < payload.len() {
+            return Err(CipherError::BufferTooSmall(payload.len()));
+        }
+
+        let out = &mut out[..payload.len()];
+        out.copy_from_slice(payload);
+        let encrypt_type = self.encrypt_type();
+        if encrypt_type != EncryptType::NO_ENC {
+            cipher.decrypt(encrypt_type, out)?;
+        }
+
+        Ok(out)
+    }
+
+    /// Consume the view and return the underlying buffer
+    pub fn into_inner(self) -> T {
+        self.buf
+    }
+
     pub fn is_valid(&self) -> bool {
+        self.is_valid_with(VersionCheck::Strict)
+    }
+
+    pub fn is_valid_with(&self, version_check: VersionCheck) -> bool {
         let buffer = self.buf.as_ref();
 
         if buffer.len() < 13 {
@@ -59,6 +229,9 @@ impl<T: AsRef<[u8]>> RMWireFrameView<T> {
         if self.header_crc8() != crate::crc::rm_s1_crc8(&buffer[0..3]) {
             return false;
         }
+        if !version_check.accept(self.version()) {
+            return false;
+        }
         if self.packet_crc16_field() != self.crc16_computed() {
             return false;
         }
@@ -87,6 +260,12 @@ impl<T: AsRef<[u8]>> RMWireFrameView<T> {
         u16::from_le_bytes([buffer[1], buffer[2] & 0b0000_0011])
     }
 
+    /// Protocol version, [`RM_S1_VERSION`] for the RM-S1
+    pub fn version(&self) -> u8 {
+        let buffer = self.buf.as_ref();
+        buffer[2] >> 2
+    }
+
     pub fn sequence_number(&self) -> u16 {
         let buffer = self.buf.as_ref();
         u16::from_le_bytes([buffer[6], buffer[7]])
@@ -102,6 +281,12 @@ impl<T: AsRef<[u8]>> RMWireFrameView<T> {
         buffer[8] & 0b0010_0000 != 0
     }
 
+    /// The sender expects an ACK once the command has been executed
+    pub fn need_ack_after_exec(&self) -> bool {
+        let buffer = self.buf.as_ref();
+        buffer[8] & 0b0100_0000 != 0
+    }
+
     pub fn encrypt_type(&self) -> EncryptType {
         let buffer = self.buf.as_ref();
         EncryptType::try_from(buffer[8] & 0b0000_0111).unwrap()
@@ -160,6 +345,19 @@ impl<T: AsRef<[u8]>> core::fmt::Debug for RMWireFrameView<T> {
 }
 
 impl<T: AsRef<[u8]> + AsMut<[u8]>> RMWireFrameView<T> {
+    pub fn set_packet_length_field(&mut self, packet_length: u16) {
+        let buffer = self.buf.as_mut();
+        let [low, high] = packet_length.to_le_bytes();
+        buffer[1] = low;
+        buffer[2] = (buffer[2] & 0b1111_1100) | (high & 0b0000_0011);
+    }
+
+    /// Set the protocol version, the header CRC8 must be updated afterwards
+    pub fn set_version(&mut self, version: u8) {
+        let buffer = self.buf.as_mut();
+        buffer[2] = (version << 2) | (buffer[2] & 0b0000_0011);
+    }
+
     pub fn set_sender_id(&mut self, sender_id: u8) {
         let buffer = self.buf.as_mut();
         buffer[4] = sender_id;
@@ -193,6 +391,15 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> RMWireFrameView<T> {
         }
     }
 
+    pub fn set_need_ack_after_exec(&mut self, need_ack_after_exec: bool) {
+        let buffer = self.buf.as_mut();
+        if need_ack_after_exec {
+            buffer[8] |= 0b0100_0000;
+        } else {
+            buffer[8] &= 0b1011_1111;
+        }
+    }
+
     pub fn set_encrypt_type(&mut self, encrypt_type: EncryptType) {
         let buffer = self.buf.as_mut();
         buffer[8] = (buffer[8] & 0b1111_1000) | (encrypt_type as u8);
@@ -229,6 +436,638 @@ impl<T: AsRef<[u8]> + AsMut<[u8]>> RMWireFrameView<T> {
         let payload_end = buffer.len() - 2;
         &mut buffer[11..payload_end]
     }
+
+    /// Decrypt the payload in place according to [`Self::encrypt_type`]
+    ///
+    /// The frame is rewritten as an unencrypted frame and the payload CRC16 is updated.
+    pub fn decrypt_payload(&mut self, cipher: &dyn FrameCipher) -> Result<(), CipherError> {
+        let encrypt_type = self.encrypt_type();
+        if encrypt_type == EncryptType::NO_ENC {
+            return Ok(());
+        }
+
+        cipher.decrypt(encrypt_type, self.payload_mut())?;
+        self.set_encrypt_type(EncryptType::NO_ENC);
+        let crc16 = self.crc16_computed();
+        self.set_packet_crc16_field(crc16);
+
+        Ok(())
+    }
+}
+
+/// A byte buffer split in two parts
+///
+/// Represents the concatenation of `head` and `tail` without copying, e.g. the contents of a
+/// DMA ring buffer that wrapped around the end of its memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SplitBuf<'a> {
+    head: &'a [u8],
+    tail: &'a [u8],
+}
+
+impl<'a> SplitBuf<'a> {
+    pub fn new(head: &'a [u8], tail: &'a [u8]) -> SplitBuf<'a> {
+        SplitBuf { head, tail }
+    }
+
+    pub fn len(&self) -> usize {
+        self.head.len() + self.tail.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<u8> {
+        if index < self.head.len() {
+            Some(self.head[index])
+        } else {
+            self.tail.get(index - self.head.len()).copied()
+        }
+    }
+
+    /// The bytes from `start` to `end`
+    ///
+    /// Panics if the range is out of bounds, like slice indexing.
+    pub fn slice(&self, start: usize, end: usize) -> SplitBuf<'a> {
+        let split = self.head.len();
+        if end <= split {
+            SplitBuf::new(&self.head[start..end], &[])
+        } else if start >= split {
+            SplitBuf::new(&self.tail[start - split..end - split], &[])
+        } else {
+            SplitBuf::new(&self.head[start..], &self.tail[..end - split])
+        }
+    }
+
+    pub fn as_slices(&self) -> (&'a [u8], &'a [u8]) {
+        (self.head, self.tail)
+    }
+
+    /// The bytes as one slice, if they are not split
+    pub fn as_contiguous(&self) -> Option<&'a [u8]> {
+        if self.tail.is_empty() {
+            Some(self.head)
+        } else if self.head.is_empty() {
+            Some(self.tail)
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u8> + 'a {
+        self.head.iter().chain(self.tail.iter()).copied()
+    }
+
+    /// Copy the bytes to the start of `out`
+    ///
+    /// Panics if `out` is shorter than [`Self::len`].
+    pub fn copy_to_slice(&self, out: &mut [u8]) {
+        out[..self.head.len()].copy_from_slice(self.head);
+        out[self.head.len()..self.len()].copy_from_slice(self.tail);
+    }
+
+    pub fn crc8_with(&self, seed: u8) -> u8 {
+        crate::crc::rm_crc8(crate::crc::rm_crc8(seed, self.head), self.tail)
+    }
+
+    pub fn crc16_with(&self, seed: u16) -> u16 {
+        crate::crc::rm_crc16(crate::crc::rm_crc16(seed, self.head), self.tail)
+    }
+}
+
+/// Frame view over a [`SplitBuf`]
+///
+/// Same accessors as [`RMWireFrameView`], for frames that wrap around a ring buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RMSplitFrameView<'a> {
+    buf: SplitBuf<'a>,
+}
+
+impl<'a> RMSplitFrameView<'a> {
+    pub fn new(buf: SplitBuf<'a>) -> RMSplitFrameView<'a> {
+        RMSplitFrameView { buf }
+    }
+
+    fn byte(&self, index: usize) -> u8 {
+        self.buf.get(index).unwrap()
+    }
+
+    pub fn as_split(&self) -> SplitBuf<'a> {
+        self.buf
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.is_valid_with(VersionCheck::Strict)
+    }
+
+    pub fn is_valid_with(&self, version_check: VersionCheck) -> bool {
+        if self.buf.len() < MIN_FRAME_LEN {
+            return false;
+        }
+        if self.byte(0) != 0x55 {
+            return false;
+        }
+        if self.buf.len() < self.packet_length_field() as usize {
+            return false;
+        }
+        if self.header_crc8() != self.crc8_computed() {
+            return false;
+        }
+        if !version_check.accept(self.version()) {
+            return false;
+        }
+        if self.packet_crc16_field() != self.crc16_computed() {
+            return false;
+        }
+
+        true
+    }
+
+    pub fn header_crc8(&self) -> u8 {
+        self.byte(3)
+    }
+
+    pub fn sender_id(&self) -> u8 {
+        self.byte(4)
+    }
+
+    pub fn receiver_id(&self) -> u8 {
+        self.byte(5)
+    }
+
+    pub fn packet_length_field(&self) -> u16 {
+        u16::from_le_bytes([self.byte(1), self.byte(2) & 0b0000_0011])
+    }
+
+    pub fn version(&self) -> u8 {
+        self.byte(2) >> 2
+    }
+
+    pub fn sequence_number(&self) -> u16 {
+        u16::from_le_bytes([self.byte(6), self.byte(7)])
+    }
+
+    pub fn is_ack(&self) -> bool {
+        self.byte(8) & 0b1000_0000 != 0
+    }
+
+    pub fn need_ack(&self) -> bool {
+        self.byte(8) & 0b0010_0000 != 0
+    }
+
+    pub fn need_ack_after_exec(&self) -> bool {
+        self.byte(8) & 0b0100_0000 != 0
+    }
+
+    pub fn encrypt_type(&self) -> EncryptType {
+        EncryptType::try_from(self.byte(8) & 0b0000_0111).unwrap()
+    }
+
+    pub fn cmd_set(&self) -> u8 {
+        self.byte(9)
+    }
+
+    pub fn cmd_id(&self) -> u8 {
+        self.byte(10)
+    }
+
+    pub fn payload(&self) -> SplitBuf<'a> {
+        self.buf.slice(HEADER_LEN, self.buf.len() - CRC16_LEN)
+    }
+
+    pub fn packet_crc16_field(&self) -> u16 {
+        let length = self.buf.len();
+        u16::from_le_bytes([self.byte(length - 2), self.byte(length - 1)])
+    }
+
+    pub fn crc16_computed(&self) -> u16 {
+        self.buf
+            .slice(0, self.buf.len() - CRC16_LEN)
+            .crc16_with(crate::crc::RM_S1_CRC16_SEED)
+    }
+
+    pub fn crc8_computed(&self) -> u8 {
+        self.buf.slice(0, 3).crc8_with(crate::crc::RM_S1_CRC8_SEED)
+    }
+
+    /// A regular view, if the frame is not split
+    pub fn to_contiguous(&self) -> Option<RMWireFrameView<&'a [u8]>> {
+        self.buf.as_contiguous().map(RMWireFrameView::new)
+    }
+
+    /// Copy the frame to the start of `out` and return a regular view
+    pub fn copy_to<'b>(
+        &self,
+        out: &'b mut [u8],
+    ) -> Result<RMWireFrameView<&'b mut [u8]>, BuildError> {
+        if out.len() < self.buf.len() {
+            return Err(BuildError::BufferTooSmall(self.buf.len()));
+        }
+
+        self.buf.copy_to_slice(out);
+        Ok(RMWireFrameView::new(&mut out[..self.buf.len()]))
+    }
+}
+
+/// Errors returned by [`RMWireFrameBuilder`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The output buffer is too small
+    ///
+    /// The usize is the number of bytes needed to hold the frame
+    BufferTooSmall(usize),
+    /// The payload does not fit in the 10-bit length field
+    ///
+    /// The usize is the length of the rejected payload
+    PayloadTooLarge(usize),
+    /// The payload could not be encrypted
+    Cipher(CipherError),
+    /// An encrypt type is set but the frame is built without a cipher
+    MissingCipher(EncryptType),
+    /// No receiver was set and the command has no known receiver
+    ///
+    /// The u8s are the CMD_SET and CMD_ID of the frame
+    NoRoute(u8, u8),
+}
+
+/// Fixed-capacity frame storage
+///
+/// Holds up to `N` bytes, of which only the first `len()` belong to the frame.
+/// Used as the backing buffer of [`RMWireFrameView`] when building frames without a caller-supplied slice.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RMWireFrameBuf<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> RMWireFrameBuf<N> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for RMWireFrameBuf<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for RMWireFrameBuf<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.buf[..self.len]
+    }
+}
+
+/// Builder for complete RM-S1 wire frames
+///
+/// Fills in the SOF, the 10-bit length, the version bits, the header CRC8 and the payload CRC16.
+///
+/// # Example
+///
+/// ```
+/// use robomaster_s1_proto::wire::RMWireFrameBuilder;
+///
+/// let mut buf = [0u8; 32];
+/// let frame = RMWireFrameBuilder::new()
+///     .sender_id(0x09)
+///     .receiver_id(0xC3)
+///     .cmd_set(0x3F)
+///     .cmd_id(0x3F)
+///     .payload(&[0x03])
+///     .build_into(&mut buf)
+///     .unwrap();
+///
+/// assert!(frame.is_valid());
+/// assert_eq!(frame.packet_length_field(), 14);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RMWireFrameBuilder<'a> {
+    sender_id: u8,
+    receiver_id: Option<u8>,
+    sequence_number: u16,
+    is_ack: bool,
+    need_ack: bool,
+    need_ack_after_exec: bool,
+    encrypt_type: EncryptType,
+    cmd_set: u8,
+    cmd_id: u8,
+    payload: &'a [u8],
+    routes: &'a [Route],
+}
+
+impl Default for RMWireFrameBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> RMWireFrameBuilder<'a> {
+    pub const fn new() -> Self {
+        RMWireFrameBuilder {
+            sender_id: 0,
+            receiver_id: None,
+            sequence_number: 0,
+            is_ack: false,
+            need_ack: false,
+            need_ack_after_exec: false,
+            encrypt_type: EncryptType::NO_ENC,
+            cmd_set: 0,
+            cmd_id: 0,
+            payload: &[],
+            routes: &[],
+        }
+    }
+
+    pub const fn sender_id(mut self, sender_id: u8) -> Self {
+        self.sender_id = sender_id;
+        self
+    }
+
+    /// Receiver of the frame
+    ///
+    /// When unset, the receiver is looked up in the routes and then in
+    /// [`default_receiver`](crate::routing::default_receiver). If neither knows the command,
+    /// building fails with [`BuildError::NoRoute`].
+    pub const fn receiver_id(mut self, receiver_id: u8) -> Self {
+        self.receiver_id = Some(receiver_id);
+        self
+    }
+
+    pub const fn receiver(self, receiver: RMS1Address) -> Self {
+        self.receiver_id(receiver.pack_id())
+    }
+
+    /// Routes overriding the default receivers
+    pub const fn routes(mut self, routes: &'a [Route]) -> Self {
+        self.routes = routes;
+        self
+    }
+
+    pub const fn sequence_number(mut self, sequence_number: u16) -> Self {
+        self.sequence_number = sequence_number;
+        self
+    }
+
+    pub const fn is_ack(mut self, is_ack: bool) -> Self {
+        self.is_ack = is_ack;
+        self
+    }
+
+    pub const fn need_ack(mut self, need_ack: bool) -> Self {
+        self.need_ack = need_ack;
+        self
+    }
+
+    pub const fn need_ack_after_exec(mut self, need_ack_after_exec: bool) -> Self {
+        self.need_ack_after_exec = need_ack_after_exec;
+        self
+    }
+
+    pub const fn encrypt_type(mut self, encrypt_type: EncryptType) -> Self {
+        self.encrypt_type = encrypt_type;
+        self
+    }
+
+    pub const fn cmd_set(mut self, cmd_set: u8) -> Self {
+        self.cmd_set = cmd_set;
+        self
+    }
+
+    pub const fn cmd_id(mut self, cmd_id: u8) -> Self {
+        self.cmd_id = cmd_id;
+        self
+    }
+
+    pub const fn payload(mut self, payload: &'a [u8]) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Total length of the frame that will be emitted
+    pub const fn frame_len(&self) -> usize {
+        MIN_FRAME_LEN + self.payload.len()
+    }
+
+    /// Receiver of the frame, either set explicitly or resolved from the routes
+    pub const fn resolved_receiver_id(&self) -> Option<u8> {
+        match self.receiver_id {
+            Some(receiver_id) => Some(receiver_id),
+            None => match resolve(self.routes, self.cmd_set, self.cmd_id) {
+                Some(receiver) => Some(receiver.pack_id()),
+                None => None,
+            },
+        }
+    }
+
+    /// The header of the frame, including the header CRC8
+    const fn header(&self, receiver_id: u8, frame_len: usize) -> [u8; HEADER_LEN] {
+        let [sequence_low, sequence_high] = self.sequence_number.to_le_bytes();
+        let flags = (self.is_ack as u8) << 7
+            | (self.need_ack_after_exec as u8) << 6
+            | (self.need_ack as u8) << 5
+            | self.encrypt_type as u8;
+
+        let mut header = [
+            0x55,
+            frame_len as u8,
+            (RM_S1_VERSION << 2) | ((frame_len >> 8) as u8 & 0b0000_0011),
+            0,
+            self.sender_id,
+            receiver_id,
+            sequence_low,
+            sequence_high,
+            flags,
+            self.cmd_set,
+            self.cmd_id,
+        ];
+        header[3] = crate::crc::rm_s1_crc8(&[header[0], header[1], header[2]]);
+        header
+    }
+
+    /// Build the frame at compile time
+    ///
+    /// `N` must be exactly [`Self::frame_len`], the receiver must be known and the encrypt type
+    /// must be [`EncryptType::NO_ENC`]. Fails to compile (or panics at runtime) otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use robomaster_s1_proto::wire::{RMWireFrameBuilder, RMWireFrameView};
+    ///
+    /// const SLOW_MODE: [u8; 14] = RMWireFrameBuilder::new()
+    ///     .sender_id(0x09)
+    ///     .cmd_set(0x3F)
+    ///     .cmd_id(0x3F)
+    ///     .payload(&[0x03])
+    ///     .build_const();
+    ///
+    /// assert!(RMWireFrameView::new(&SLOW_MODE).is_valid());
+    /// ```
+    pub const fn build_const<const N: usize>(&self) -> [u8; N] {
+        assert!(N == self.frame_len(), "N must be the length of the frame");
+        assert!(
+            matches!(self.encrypt_type, EncryptType::NO_ENC),
+            "const frames cannot be encrypted"
+        );
+        assert!(N <= MAX_FRAME_LEN, "payload too large");
+
+        let receiver_id = match self.resolved_receiver_id() {
+            Some(receiver_id) => receiver_id,
+            None => panic!("no receiver for the command"),
+        };
+        let header = self.header(receiver_id, N);
+        let mut frame = [0u8; N];
+        let mut i = 0;
+        while i < HEADER_LEN {
+            frame[i] = header[i];
+            i += 1;
+        }
+        let mut i = 0;
+        while i < self.payload.len() {
+            frame[HEADER_LEN + i] = self.payload[i];
+            i += 1;
+        }
+
+        let crc16 =
+            crate::crc::rm_crc16(crate::crc::rm_s1_crc16(&header), self.payload).to_le_bytes();
+        frame[N - 2] = crc16[0];
+        frame[N - 1] = crc16[1];
+        frame
+    }
+
+    /// Write the frame to the start of `buf`
+    ///
+    /// Returns a view over exactly the bytes of the frame. Frames with an encrypt type are
+    /// rejected, use [`Self::build_into_with_cipher`] for them.
+    pub fn build_into<'b>(
+        &self,
+        buf: &'b mut [u8],
+    ) -> Result<RMWireFrameView<&'b mut [u8]>, BuildError> {
+        self.build_into_inner(buf, None)
+    }
+
+    /// Write the frame to the start of `buf`, encrypting the payload with `cipher`
+    ///
+    /// The payload is encrypted according to the encrypt type of the builder.
+    pub fn build_into_with_cipher<'b>(
+        &self,
+        buf: &'b mut [u8],
+        cipher: &dyn FrameCipher,
+    ) -> Result<RMWireFrameView<&'b mut [u8]>, BuildError> {
+        self.build_into_inner(buf, Some(cipher))
+    }
+
+    fn build_into_inner<'b>(
+        &self,
+        buf: &'b mut [u8],
+        cipher: Option<&dyn FrameCipher>,
+    ) -> Result<RMWireFrameView<&'b mut [u8]>, BuildError> {
+        let frame_len = self.frame_len();
+        if frame_len > MAX_FRAME_LEN {
+            return Err(BuildError::PayloadTooLarge(self.payload.len()));
+        }
+        if buf.len() < frame_len {
+            return Err(BuildError::BufferTooSmall(frame_len));
+        }
+
+        let mut frame = RMWireFrameView::new(&mut buf[..frame_len]);
+        self.write(&mut frame, cipher)?;
+        Ok(frame)
+    }
+
+    /// Write a frame whose payload of `payload_len` bytes is filled in place by `fill`
+    ///
+    /// The payload set on the builder is ignored. The payload cannot be encrypted, an encrypt
+    /// type other than [`EncryptType::NO_ENC`] is rejected. Used by the command builders to
+    /// avoid assembling the payload in a separate buffer.
+    pub fn build_into_with<'b>(
+        &self,
+        buf: &'b mut [u8],
+        payload_len: usize,
+        fill: impl FnOnce(&mut [u8]),
+    ) -> Result<RMWireFrameView<&'b mut [u8]>, BuildError> {
+        if self.encrypt_type != EncryptType::NO_ENC {
+            return Err(BuildError::MissingCipher(self.encrypt_type));
+        }
+        let frame_len = MIN_FRAME_LEN + payload_len;
+        if frame_len > MAX_FRAME_LEN {
+            return Err(BuildError::PayloadTooLarge(payload_len));
+        }
+        if buf.len() < frame_len {
+            return Err(BuildError::BufferTooSmall(frame_len));
+        }
+        let receiver_id = self
+            .resolved_receiver_id()
+            .ok_or(BuildError::NoRoute(self.cmd_set, self.cmd_id))?;
+
+        let mut frame = RMWireFrameView::new(&mut buf[..frame_len]);
+        frame.buf[..HEADER_LEN].copy_from_slice(&self.header(receiver_id, frame_len));
+        fill(frame.payload_mut());
+        let crc16 = frame.crc16_computed();
+        frame.set_packet_crc16_field(crc16);
+        Ok(frame)
+    }
+
+    /// Write the frame to a fixed-capacity buffer of `N` bytes
+    pub fn build<const N: usize>(&self) -> Result<RMWireFrameView<RMWireFrameBuf<N>>, BuildError> {
+        self.build_inner(None)
+    }
+
+    /// Write the frame to a fixed-capacity buffer of `N` bytes, encrypting the payload with `cipher`
+    pub fn build_with_cipher<const N: usize>(
+        &self,
+        cipher: &dyn FrameCipher,
+    ) -> Result<RMWireFrameView<RMWireFrameBuf<N>>, BuildError> {
+        self.build_inner(Some(cipher))
+    }
+
+    fn build_inner<const N: usize>(
+        &self,
+        cipher: Option<&dyn FrameCipher>,
+    ) -> Result<RMWireFrameView<RMWireFrameBuf<N>>, BuildError> {
+        let frame_len = self.frame_len();
+        if frame_len > MAX_FRAME_LEN {
+            return Err(BuildError::PayloadTooLarge(self.payload.len()));
+        }
+        if N < frame_len {
+            return Err(BuildError::BufferTooSmall(frame_len));
+        }
+
+        let mut frame = RMWireFrameView::new(RMWireFrameBuf {
+            buf: [0u8; N],
+            len: frame_len,
+        });
+        self.write(&mut frame, cipher)?;
+        Ok(frame)
+    }
+
+    fn write<T: AsRef<[u8]> + AsMut<[u8]>>(
+        &self,
+        frame: &mut RMWireFrameView<T>,
+        cipher: Option<&dyn FrameCipher>,
+    ) -> Result<(), BuildError> {
+        let receiver_id = self
+            .resolved_receiver_id()
+            .ok_or(BuildError::NoRoute(self.cmd_set, self.cmd_id))?;
+        let frame_len = frame.buf.as_ref().len();
+        frame.buf.as_mut()[..HEADER_LEN].copy_from_slice(&self.header(receiver_id, frame_len));
+        frame.set_payload(self.payload);
+        if self.encrypt_type != EncryptType::NO_ENC {
+            cipher
+                .ok_or(BuildError::MissingCipher(self.encrypt_type))?
+                .encrypt(self.encrypt_type, frame.payload_mut())
+                .map_err(BuildError::Cipher)?;
+        }
+        let crc16 = frame.crc16_computed();
+        frame.set_packet_crc16_field(crc16);
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -253,8 +1092,8 @@ mod tests {
         assert_eq!(frame.receiver_id(), 0x1d);
         assert_eq!(frame.packet_length_field(), 0x24);
         assert_eq!(frame.sequence_number(), 0);
-        assert_eq!(frame.is_ack(), false);
-        assert_eq!(frame.need_ack(), false);
+        assert!(!frame.is_ack());
+        assert!(!frame.need_ack());
         assert_eq!(frame.cmd_set(), 0x00);
         assert_eq!(frame.cmd_id(), 0xf0);
         let expected_str = b"\0mic:hz err,value:511\r\n";
@@ -269,7 +1108,7 @@ mod tests {
         ];
         let frame = RMWireFrameView::new(&buf);
 
-        assert_eq!(frame.is_valid(), false);
+        assert!(!frame.is_valid());
     }
 
     #[test]
@@ -280,7 +1119,7 @@ mod tests {
 
         let frame = RMWireFrameView::new(&buf);
 
-        assert_eq!(frame.is_valid(), false);
+        assert!(!frame.is_valid());
     }
 
     #[test]
@@ -296,7 +1135,7 @@ mod tests {
         let crc16 = crate::crc::rm_crc16(0x3692, &buf[..buf.len() - 2]);
         assert_eq!(crc16, frame.packet_crc16_field());
 
-        assert_eq!(frame.is_valid(), true);
+        assert!(frame.is_valid());
     }
 
     #[test]
@@ -334,8 +1173,8 @@ mod tests {
         assert_eq!(frame.receiver_id(), 0xC3);
         assert_eq!(frame.packet_length_field(), 0x1B);
         assert_eq!(frame.sequence_number(), 0xE0);
-        assert_eq!(frame.is_ack(), false);
-        assert_eq!(frame.need_ack(), false);
+        assert!(!frame.is_ack());
+        assert!(!frame.need_ack());
         assert_eq!(frame.encrypt_type(), EncryptType::NO_ENC);
         assert_eq!(
             frame.cmd_set(),
@@ -412,7 +1251,7 @@ mod tests {
             crate::duss::cmd_set_rm::RMCommandType::SET_CHASSIS_SPEED as u8
         );
         assert_eq!(frame.payload(), &[0x3]);
-        assert_eq!(frame.need_ack(), false);
+        assert!(!frame.need_ack());
     }
 
     #[test]
@@ -565,4 +1404,288 @@ mod tests {
             is_ack: false, need_ack: false, cmd_set: 0, cmd_id: F0, payload: [], packet_crc16_field: 0 }"
         );
     }
+
+    #[test]
+    fn test_new_checked() {
+        let buf = [
+            0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+        ];
+        assert!(RMWireFrameView::new_checked(&buf).is_ok());
+
+        assert_eq!(
+            RMWireFrameView::new_checked(&buf[..12]).err(),
+            Some(FrameError::TooShort(12))
+        );
+
+        let mut bad_sof = buf;
+        bad_sof[0] = 0x35;
+        assert_eq!(
+            RMWireFrameView::new_checked(&bad_sof).err(),
+            Some(FrameError::NoStartOfFrame)
+        );
+
+        let mut bad_header = buf;
+        bad_header[3] = 0x00;
+        assert_eq!(
+            RMWireFrameView::new_checked(&bad_header).err(),
+            Some(FrameError::InvalidHeaderCRC)
+        );
+
+        let mut too_long = [0u8; 15];
+        too_long[..14].copy_from_slice(&buf);
+        assert_eq!(
+            RMWireFrameView::new_checked(&too_long).err(),
+            Some(FrameError::LengthMismatch(14, 15))
+        );
+
+        let mut bad_crc = buf;
+        bad_crc[11] = 0x02;
+        assert_eq!(
+            RMWireFrameView::new_checked(&bad_crc).err(),
+            Some(FrameError::InvalidPacketCRC)
+        );
+    }
+
+    #[test]
+    fn test_version() {
+        let mut buf = [
+            0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+        ];
+        let frame = RMWireFrameView::new(&buf);
+        assert_eq!(frame.version(), RM_S1_VERSION);
+
+        let mut frame = RMWireFrameView::new(&mut buf);
+        frame.set_version(2);
+        frame.set_header_crc8();
+        let crc16 = frame.crc16_computed();
+        frame.set_packet_crc16_field(crc16);
+
+        assert_eq!(frame.version(), 2);
+        assert_eq!(frame.packet_length_field(), 0x0E);
+        assert!(!frame.is_valid());
+        assert!(frame.is_valid_with(VersionCheck::Ignore));
+        assert_eq!(frame.check(), Err(FrameError::UnknownVersion(2)));
+        assert_eq!(frame.check_with(VersionCheck::Ignore), Ok(()));
+
+        fn warn(version: u8) {
+            assert_eq!(version, 2);
+        }
+        assert!(frame.is_valid_with(VersionCheck::Warn(warn)));
+    }
+
+    #[test]
+    fn test_builder_heartbeat_msg() {
+        let expected = [
+            0x55, 0x1B, 0x04, 0x75, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x3F, 0x60, 0x00, 0x04, 0x20,
+            0x00, 0x01, 0x00, 0x40, 0x00, 0x02, 0x10, 0x04, 0x03, 0x00, 0x04, 0xFA, 0xF0,
+        ];
+
+        let mut buf = [0u8; 64];
+        let frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0xC3)
+            .sequence_number(0xE0)
+            .cmd_set(crate::duss::cmd_set_types::CommandSetType::RM as u8)
+            .cmd_id(crate::duss::cmd_set_rm::RMCommandType::FC_RMC as u8)
+            .payload(&expected[11..25])
+            .build_into(&mut buf)
+            .unwrap();
+
+        assert!(frame.is_valid());
+        assert_eq!(frame.as_bytes(), &expected);
+    }
+
+    #[test]
+    fn test_builder_ack_flags() {
+        let expected = [
+            0x55, 0x0E, 0x04, 0x66, 0x09, 0x03, 0x4E, 0x06, 0xA0, 0x48, 0x08, 0x01, 0xC2, 0xE8,
+        ];
+
+        let frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0x03)
+            .sequence_number(0x064E)
+            .is_ack(true)
+            .need_ack(true)
+            .cmd_set(0x48)
+            .cmd_id(0x08)
+            .payload(&[0x01])
+            .build::<32>()
+            .unwrap();
+
+        assert!(frame.is_valid());
+        assert_eq!(frame.as_bytes().len(), expected.len());
+        assert_eq!(frame.as_bytes(), &expected);
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let mut buf = [0u8; 13];
+        let result = RMWireFrameBuilder::new()
+            .payload(&[0x01])
+            .build_into(&mut buf);
+        assert_eq!(result.err(), Some(BuildError::BufferTooSmall(14)));
+
+        let payload = [0u8; MAX_FRAME_LEN];
+        let result = RMWireFrameBuilder::new().payload(&payload).build::<2048>();
+        assert_eq!(
+            result.err(),
+            Some(BuildError::PayloadTooLarge(MAX_FRAME_LEN))
+        );
+    }
+
+    #[test]
+    fn test_split_frame_view() {
+        let buf = [
+            0x55, 0x1B, 0x04, 0x75, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x3F, 0x60, 0x00, 0x04, 0x20,
+            0x00, 0x01, 0x00, 0x40, 0x00, 0x02, 0x10, 0x04, 0x03, 0x00, 0x04, 0xFA, 0xF0,
+        ];
+
+        for split in 0..=buf.len() {
+            let frame = RMSplitFrameView::new(SplitBuf::new(&buf[..split], &buf[split..]));
+
+            assert!(frame.is_valid());
+            assert_eq!(frame.sender_id(), 0x09);
+            assert_eq!(frame.receiver_id(), 0xC3);
+            assert_eq!(frame.packet_length_field(), 0x1B);
+            assert_eq!(frame.sequence_number(), 0xE0);
+            assert_eq!(frame.crc8_computed(), 0x75);
+            assert_eq!(frame.crc16_computed(), 0xF0FA);
+            assert!(frame.payload().iter().eq(buf[11..25].iter().copied()));
+            assert_eq!(
+                frame.to_contiguous().is_some(),
+                split == 0 || split == buf.len()
+            );
+
+            let mut out = [0u8; 32];
+            let copy = frame.copy_to(&mut out).unwrap();
+            assert_eq!(copy.as_bytes(), &buf);
+        }
+    }
+
+    use crate::frames::{
+        GIMBAL_MODE_FREE, ROBOSTACK_ADD_NODE, ROBOSTACK_ADD_NODE_AGAIN, ROBOSTACK_ADD_SUB_BATTERY,
+        ROBOSTACK_ADD_SUB_TURRET, SLOW_MODE_ENTER,
+    };
+
+    /// Compare against a capture whose CRC bytes were blanked out
+    fn assert_matches_capture(frame: &[u8], capture: &[u8]) {
+        assert!(RMWireFrameView::new(frame).is_valid());
+        assert_eq!(frame.len(), capture.len());
+        assert_eq!(frame[..3], capture[..3]);
+        assert_eq!(frame[4..frame.len() - 2], capture[4..capture.len() - 2]);
+    }
+
+    #[test]
+    fn test_build_const() {
+        assert_matches_capture(
+            &SLOW_MODE_ENTER,
+            &[
+                0x55, 0x0E, 0x04, 0xFF, 0x09, 0xC3, 0xFF, 0xFF, 0x40, 0x3F, 0x3F, 0x03, 0xFF, 0xFF,
+            ],
+        );
+        assert!(RMWireFrameView::new(&SLOW_MODE_ENTER).need_ack_after_exec());
+
+        assert_matches_capture(
+            &ROBOSTACK_ADD_NODE,
+            &[
+                0x55, 0x12, 0x04, 0xFF, 0x09, 0x03, 0x01, 0x00, 0x40, 0x48, 0x01, 0x09, 0x00, 0x00,
+                0x00, 0x03, 0xFF, 0xFF,
+            ],
+        );
+        assert_matches_capture(
+            &ROBOSTACK_ADD_SUB_BATTERY,
+            &[
+                0x55, 0x1C, 0x04, 0xFF, 0x09, 0x03, 0x02, 0x00, 0x40, 0x48, 0x03, 0x09, 0x00, 0x03,
+                0x00, 0x01, 0xFB, 0xDC, 0xF5, 0xD7, 0x03, 0x00, 0x02, 0x00, 0x01, 0x00, 0xFF, 0xFF,
+            ],
+        );
+        assert_matches_capture(
+            &ROBOSTACK_ADD_NODE_AGAIN,
+            &[
+                0x55, 0x12, 0x04, 0xFF, 0x09, 0x03, 0x03, 0x00, 0x40, 0x48, 0x01, 0x09, 0x00, 0x00,
+                0x00, 0x03, 0xFF, 0xFF,
+            ],
+        );
+        assert_matches_capture(
+            &ROBOSTACK_ADD_SUB_TURRET,
+            &[
+                0x55, 0x24, 0x04, 0xFF, 0x09, 0x03, 0x04, 0x00, 0x40, 0x48, 0x03, 0x09, 0x01, 0x03,
+                0x00, 0x02, 0xA7, 0x02, 0x29, 0x88, 0x03, 0x00, 0x02, 0x00, 0x66, 0x3E, 0x3E, 0x4C,
+                0x03, 0x00, 0x02, 0x00, 0x32, 0x00, 0xFF, 0xFF,
+            ],
+        );
+
+        assert_eq!(
+            GIMBAL_MODE_FREE,
+            [
+                0x55, 0x0F, 0x04, 0xa2, 0x09, 0xC3, 0xE0, 0x00, 0x00, 0x04, 0x4C, 0x00, 0x00, 0x6c,
+                0xe1,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_const_matches_builder() {
+        let frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .receiver_id(0x03)
+            .sequence_number(0x0002)
+            .need_ack_after_exec(true)
+            .cmd_set(0x48)
+            .cmd_id(0x03)
+            .payload(&ROBOSTACK_ADD_SUB_BATTERY[11..26])
+            .build::<64>()
+            .unwrap();
+
+        assert_eq!(frame.as_bytes(), &ROBOSTACK_ADD_SUB_BATTERY);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_build_const_wrong_length() {
+        let _: [u8; 15] = RMWireFrameBuilder::new().payload(&[0x01]).build_const();
+    }
+
+    #[test]
+    fn test_builder_routing() {
+        use crate::routing::Route;
+
+        let frame = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .cmd_set(crate::duss::cmd_set_types::CommandSetType::RM as u8)
+            .cmd_id(crate::duss::cmd_set_rm::RMCommandType::SHOOT_CMD as u8)
+            .payload(&[0x01])
+            .build::<32>()
+            .unwrap();
+        assert_eq!(frame.receiver_id(), 0x17);
+
+        let routes = [Route::cmd_set(
+            crate::duss::cmd_set_types::CommandSetType::RM as u8,
+            RMS1Address::armor2_id,
+        )];
+        let builder = RMWireFrameBuilder::new()
+            .sender_id(0x09)
+            .cmd_set(crate::duss::cmd_set_types::CommandSetType::RM as u8)
+            .cmd_id(crate::duss::cmd_set_rm::RMCommandType::ARMOR_LED_SET as u8)
+            .payload(&[0x01]);
+        assert_eq!(builder.resolved_receiver_id(), Some(0x18));
+        assert_eq!(
+            builder.clone().routes(&routes).resolved_receiver_id(),
+            Some(0x58)
+        );
+        assert_eq!(
+            builder
+                .routes(&routes)
+                .receiver(RMS1Address::armor5_id)
+                .resolved_receiver_id(),
+            Some(0xB8)
+        );
+
+        let result = RMWireFrameBuilder::new()
+            .cmd_set(crate::duss::cmd_set_types::CommandSetType::CAMERA as u8)
+            .cmd_id(0x01)
+            .build::<32>();
+        assert_eq!(result.err(), Some(BuildError::NoRoute(0x02, 0x01)));
+    }
 }